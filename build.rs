@@ -0,0 +1,63 @@
+use std::{env, fmt::Write, fs, path::Path};
+use unicode_normalization::UnicodeNormalization;
+
+const DATA: &str = "data/ipa.tsv";
+
+fn category(name: &str) -> Option<&'static str> {
+    match name {
+        "pulmonic" => Some("Pulmonic"),
+        "non-pulmonic" => Some("NonPulmonic"),
+        "other" => Some("Other"),
+        "vowel" => Some("Vowel"),
+        _ => None,
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", DATA);
+    let data = fs::read_to_string(DATA).expect("could not read the IPA table");
+
+    let mut entries = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split('\t');
+        let (symbol, name) = match (fields.next(), fields.next(), fields.next())
+        {
+            (Some(symbol), Some(name), None) => (symbol, name),
+            _ => panic!("{}:{}: expected `symbol<TAB>category`", DATA, i + 1),
+        };
+        let category = category(name).unwrap_or_else(|| {
+            panic!("{}:{}: unknown chart category {}", DATA, i + 1, name)
+        });
+        entries.push((symbol.nfc().collect::<String>(), category, i + 1));
+    }
+
+    entries.sort_by(|(a, _, i), (b, _, j)| (a, i).cmp(&(b, j)));
+    for pair in entries.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            panic!(
+                "{}:{}: duplicate symbol {}, previously listed on line {}",
+                DATA, pair[1].2, pair[1].0, pair[0].2
+            );
+        }
+    }
+
+    let mut code = String::from("pub const SYMBOLS: &[Entry] = &[\n");
+    for (symbol, category, _) in &entries {
+        writeln!(
+            code,
+            "    Entry {{ symbol: {:?}, category: Category::{} }},",
+            symbol, category
+        )
+        .expect("String as fmt::Writer cannot fail");
+    }
+    code.push_str("];\n");
+
+    let out = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out).join("ipa.rs"), code)
+        .expect("could not write the IPA table");
+}
@@ -0,0 +1,170 @@
+use crate::{pat::CaptureName, source::Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name {
+    pub content: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub decls: Vec<Decl>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decl {
+    Alphabet(AlphabetDecl),
+    Class(ClassDecl),
+    Feature(FeatureDecl),
+    Rule(RuleDecl),
+    Block(BlockDecl),
+}
+
+impl Decl {
+    pub fn span(&self) -> &Span {
+        match self {
+            Decl::Alphabet(decl) => &decl.span,
+            Decl::Class(decl) => &decl.span,
+            Decl::Feature(decl) => &decl.span,
+            Decl::Rule(decl) => &decl.span,
+            Decl::Block(decl) => &decl.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlphabetDecl {
+    pub symbols: Vec<Name>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassDecl {
+    pub name: Name,
+    pub expr: ClassExpr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassExprKind {
+    Symbol(String),
+    Class(String),
+    Group(Box<ClassExpr>),
+    Not(Box<ClassExpr>),
+    Diff(Box<ClassExpr>, Box<ClassExpr>),
+    Intersect(Box<ClassExpr>, Box<ClassExpr>),
+    Or(Box<ClassExpr>, Box<ClassExpr>),
+    Matrix(Vec<FeatureSpec>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassExpr {
+    pub kind: ClassExprKind,
+    pub span: Span,
+}
+
+// Binary features name their values `+` and `-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureDecl {
+    pub name: Name,
+    pub values: Vec<FeatureValue>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureValue {
+    pub value: Name,
+    pub members: ClassExpr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureSpec {
+    pub value: bool,
+    pub name: Name,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleModifierKind {
+    Ltr,
+    Rtl,
+    Feeding,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleModifier {
+    pub kind: RuleModifierKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleDecl {
+    pub modifiers: Vec<RuleModifier>,
+    pub target: PatternExpr,
+    pub replacement: Vec<ReplacementExpr>,
+    pub env: Option<Env>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockMode {
+    Sequential,
+    Simultaneous,
+    Iterative,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDecl {
+    pub mode: BlockMode,
+    pub rules: Vec<RuleDecl>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplacementExprKind {
+    Null,
+    Symbol(String),
+    Class(PatternExpr),
+    Ref(PatternExpr, CaptureName),
+    Matrix(Vec<FeatureSpec>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacementExpr {
+    pub kind: ReplacementExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Env {
+    pub left: Option<PatternExpr>,
+    pub right: Option<PatternExpr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternExprKind {
+    Null,
+    Symbol(String),
+    Class(String),
+    Boundary,
+    Group(Box<PatternExpr>),
+    Optional(Box<PatternExpr>),
+    Star(Box<PatternExpr>),
+    Plus(Box<PatternExpr>),
+    Repeat(Box<PatternExpr>, usize, Option<usize>),
+    Capture(Box<PatternExpr>, CaptureName),
+    Not(Box<PatternExpr>),
+    Diff(Box<PatternExpr>, Box<PatternExpr>),
+    Intersect(Box<PatternExpr>, Box<PatternExpr>),
+    And(Box<PatternExpr>, Box<PatternExpr>),
+    Or(Box<PatternExpr>, Box<PatternExpr>),
+    Matrix(Vec<FeatureSpec>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternExpr {
+    pub kind: PatternExprKind,
+    pub span: Span,
+}
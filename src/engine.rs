@@ -0,0 +1,823 @@
+use crate::{
+    error::{Diagnostic, ErrorKind},
+    pat::{Match, Pattern},
+    rule::Rule,
+    symbol::{Terminal, Word},
+};
+
+pub const MAX_ITERATIONS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Mode {
+    #[default]
+    Sequential,
+    Simultaneous,
+    Iterative,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Block {
+    pub mode: Mode,
+    pub rules: Vec<Rule>,
+}
+
+impl Block {
+    pub fn new<V>(mode: Mode, rules: V) -> Self
+    where
+        V: Into<Vec<Rule>>,
+    {
+        Self { mode, rules: rules.into() }
+    }
+
+    pub fn apply(&self, word: &Word, errs: &mut Diagnostic) -> Word {
+        match self.mode {
+            Mode::Sequential => apply_sequential(&self.rules, word),
+            Mode::Simultaneous => apply_simultaneous(&self.rules, word),
+            Mode::Iterative => apply_iterative(&self.rules, word, errs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Engine {
+    blocks: Vec<Block>,
+}
+
+impl Engine {
+    pub fn new<V>(rules: V) -> Self
+    where
+        V: Into<Vec<Rule>>,
+    {
+        Self::with_blocks(vec![Block::new(Mode::Sequential, rules)])
+    }
+
+    pub fn with_blocks<V>(blocks: V) -> Self
+    where
+        V: Into<Vec<Block>>,
+    {
+        Self { blocks: blocks.into() }
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    pub fn apply(&self, word: &Word, errs: &mut Diagnostic) -> Word {
+        let mut word = word.clone();
+        for block in &self.blocks {
+            word = block.apply(&word, errs);
+        }
+        word
+    }
+}
+
+pub fn apply_rule(rule: &Rule, word: &Word) -> Word {
+    match (rule.direction, rule.feeding) {
+        (Direction::Ltr, false) => {
+            apply_simultaneous(std::slice::from_ref(rule), word)
+        },
+        (Direction::Rtl, false) => apply_rtl(rule, word),
+        (Direction::Ltr, true) => feed_ltr(rule, word),
+        (Direction::Rtl, true) => feed_rtl(rule, word),
+    }
+}
+
+// Non-feeding application matches every position against the original input
+// and only then splices the replacements in. Scanning from the right means a
+// match near the end wins over an overlapping match further left.
+fn apply_rtl(rule: &Rule, word: &Word) -> Word {
+    let input = &word.symbols;
+    let mut matches = Vec::new();
+    let mut limit = input.len();
+
+    for start in (0 ..= input.len()).rev() {
+        if let Some(found) = match_rule_at(rule, input, start) {
+            if found.general_end() <= limit {
+                limit = start;
+                matches.push(found);
+            }
+        }
+    }
+
+    let mut symbols = Vec::with_capacity(input.len());
+    let mut pos = 0;
+    for found in matches.iter().rev() {
+        symbols.extend_from_slice(&input[pos .. found.general_start()]);
+        symbols.extend(rule.replace(input, found));
+        pos = found.general_end();
+    }
+
+    symbols.extend_from_slice(&input[pos ..]);
+    Word { symbols }
+}
+
+// Feeding application rewrites the word in place, so the output of one match
+// is visible to the environments of the following ones. The scan continues
+// after the replaced symbols, which keeps a rule from rewriting its own output.
+fn feed_ltr(rule: &Rule, word: &Word) -> Word {
+    let mut symbols = word.symbols.clone();
+    let mut start = 0;
+
+    while start <= symbols.len() {
+        match match_rule_at(rule, &symbols, start) {
+            Some(found) => {
+                let replaced = rule.replace(&symbols, &found);
+                let end = found.general_end();
+                let skip = if end == start { 1 } else { 0 };
+                start += replaced.len() + skip;
+                symbols.splice(found.general_start() .. end, replaced);
+            },
+            None => start += 1,
+        }
+    }
+
+    Word { symbols }
+}
+
+// Scanning from the right, a match may not reach into the symbols replaced
+// after it, which keeps a rule from rewriting its own output as in `feed_ltr`.
+fn feed_rtl(rule: &Rule, word: &Word) -> Word {
+    let mut symbols = word.symbols.clone();
+    let mut limit = symbols.len();
+
+    for start in (0 ..= symbols.len()).rev() {
+        if let Some(found) = match_rule_at(rule, &symbols, start) {
+            if found.general_end() <= limit {
+                let replaced = rule.replace(&symbols, &found);
+                symbols.splice(
+                    found.general_start() .. found.general_end(),
+                    replaced,
+                );
+                limit = start;
+            }
+        }
+    }
+
+    Word { symbols }
+}
+
+fn apply_sequential(rules: &[Rule], word: &Word) -> Word {
+    let mut word = word.clone();
+    for rule in rules {
+        word = apply_rule(rule, &word);
+    }
+    word
+}
+
+// Every rule sees the same input, and at each position the first rule that
+// matches wins, so `a > e` and `e > i` together shift `ae` to `ei`. Rules in a
+// simultaneous block always scan left to right without feeding.
+fn apply_simultaneous(rules: &[Rule], word: &Word) -> Word {
+    let input = &word.symbols;
+    let mut symbols = Vec::with_capacity(input.len());
+    let mut pos = 0;
+
+    for start in 0 ..= input.len() {
+        if start < pos {
+            continue;
+        }
+        let found = rules.iter().find_map(|rule| {
+            match_rule_at(rule, input, start).map(|found| (rule, found))
+        });
+        if let Some((rule, found)) = found {
+            symbols.extend_from_slice(&input[pos .. start]);
+            symbols.extend(rule.replace(input, &found));
+            pos = found.general_end();
+        }
+    }
+
+    symbols.extend_from_slice(&input[pos ..]);
+    Word { symbols }
+}
+
+fn apply_iterative(rules: &[Rule], word: &Word, errs: &mut Diagnostic) -> Word {
+    let mut seen = vec![word.clone()];
+    let mut curr = word.clone();
+
+    for iteration in 1 ..= MAX_ITERATIONS {
+        let next = apply_sequential(rules, &curr);
+        if next == curr {
+            return next;
+        }
+        if seen.contains(&next) {
+            errs.warn(ErrorKind::NoFixedPoint(word.to_string(), iteration));
+            return next;
+        }
+        seen.push(next.clone());
+        curr = next;
+    }
+
+    errs.warn(ErrorKind::NoFixedPoint(word.to_string(), MAX_ITERATIONS));
+    curr
+}
+
+// The left environment is matched first so that its captures bind the target,
+// and both bind the right environment. The returned target match carries the
+// captures of the whole rule.
+fn match_rule_at(
+    rule: &Rule,
+    input: &[Terminal],
+    start: usize,
+) -> Option<Match> {
+    let lefts = match &rule.left {
+        Some(left) => matches_before(left, input, start),
+        None => vec![Match::default()],
+    };
+
+    for left in &lefts {
+        let mut targets =
+            rule.target.match_all_bound(input, start, &left.captures);
+        targets.sort_by_key(|found| std::cmp::Reverse(found.general_end()));
+
+        for mut found in targets {
+            let end = found.general_end();
+
+            // Only insertions may replace the empty string, and they do so at
+            // most once per position since matches are searched on the input.
+            if end == start && !rule.is_insertion() {
+                continue;
+            }
+
+            let mut captures = left.captures.clone();
+            captures.append(&mut found.captures);
+
+            let right = match &rule.right {
+                Some(right) => right
+                    .match_all_bound(input, end, &captures)
+                    .into_iter()
+                    .next(),
+                None => Some(Match::default()),
+            };
+
+            if let Some(mut right) = right {
+                captures.append(&mut right.captures);
+                found.captures = captures;
+                return Some(found);
+            }
+        }
+    }
+
+    None
+}
+
+fn matches_before(pat: &Pattern, input: &[Terminal], end: usize) -> Vec<Match> {
+    (0 ..= end)
+        .rev()
+        .flat_map(|start| pat.match_all_at(input, start))
+        .filter(|found| found.general_end() == end)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_rule, Block, Direction, Engine, Mode, MAX_ITERATIONS};
+    use crate::{
+        error::{Diagnostic, ErrorKind},
+        find_syms,
+        make_terms,
+        pat::{CaptureName, Pattern},
+        rule::{Replacement, Rule},
+        symbol::{NonTerminal, Symbol, Table, Terminal, Word},
+    };
+
+    fn terms_for_test() -> Table<Terminal> {
+        make_terms!("a", "i", "p", "f", "t", "s")
+    }
+
+    fn wrap(term: &Terminal) -> Symbol {
+        Symbol::Term(term.clone())
+    }
+
+    fn word(terms: &Table<Terminal>, descs: &[&str]) -> Word {
+        let symbols = descs
+            .iter()
+            .map(|desc| terms.find(desc).unwrap().clone())
+            .collect();
+        Word { symbols }
+    }
+
+    fn terms_pat(terms: &Table<Terminal>, descs: &[&str]) -> Pattern {
+        Pattern::Terms(word(terms, descs).symbols)
+    }
+
+    fn replacement(
+        terms: &Table<Terminal>,
+        descs: &[&str],
+    ) -> Vec<Replacement> {
+        word(terms, descs).symbols.into_iter().map(Replacement::Term).collect()
+    }
+
+    #[test]
+    fn unconditioned() {
+        let terms = terms_for_test();
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["p"]),
+            replacement: replacement(&terms, &["f"]),
+            left: None,
+            right: None,
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["p", "a", "p", "i"])),
+            word(&terms, &["f", "a", "f", "i"])
+        );
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["t", "a"])),
+            word(&terms, &["t", "a"])
+        );
+    }
+
+    #[test]
+    fn with_env() {
+        let terms = terms_for_test();
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["p"]),
+            replacement: replacement(&terms, &["f"]),
+            left: Some(terms_pat(&terms, &["a"])),
+            right: Some(terms_pat(&terms, &["a"])),
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["a", "p", "a", "p", "a"])),
+            word(&terms, &["a", "f", "a", "f", "a"])
+        );
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["p", "a", "p", "i"])),
+            word(&terms, &["p", "a", "p", "i"])
+        );
+    }
+
+    #[test]
+    fn class_env_and_multiple_symbols() {
+        let terms = terms_for_test();
+        let vowels = find_syms!(&terms, wrap, "a", "i").unwrap();
+        let vowel = NonTerminal::new("V", vowels);
+
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["t", "s"]),
+            replacement: replacement(&terms, &["s"]),
+            left: Some(Pattern::NonTerm(vowel)),
+            right: None,
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["i", "t", "s", "a", "t", "s"])),
+            word(&terms, &["i", "s", "a", "s"])
+        );
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["t", "s", "a"])),
+            word(&terms, &["t", "s", "a"])
+        );
+    }
+
+    #[test]
+    fn class_correspondence() {
+        let terms = terms_for_test();
+        let stops = find_syms!(&terms, wrap, "p", "t").unwrap();
+        let fricatives = find_syms!(&terms, wrap, "f", "s").unwrap();
+
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::NonTerm(NonTerminal::new("P", stops)),
+            replacement: vec![Replacement::Class(NonTerminal::new(
+                "F", fricatives,
+            ))],
+            left: Some(Pattern::NonTerm(NonTerminal::new(
+                "V",
+                find_syms!(&terms, wrap, "a", "i").unwrap(),
+            ))),
+            right: None,
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["a", "p", "i", "t", "t"])),
+            word(&terms, &["a", "f", "i", "s", "t"])
+        );
+    }
+
+    #[test]
+    fn deletion() {
+        let terms = terms_for_test();
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["t"]),
+            replacement: Vec::new(),
+            left: None,
+            right: Some(terms_pat(&terms, &["s"])),
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["t", "s", "a", "t", "t", "s"])),
+            word(&terms, &["s", "a", "t", "s"])
+        );
+    }
+
+    #[test]
+    fn epenthesis() {
+        let terms = terms_for_test();
+        let consonants = find_syms!(&terms, wrap, "p", "t", "s").unwrap();
+        let consonant = NonTerminal::new("C", consonants);
+
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::Terms(Vec::new()),
+            replacement: replacement(&terms, &["i"]),
+            left: Some(Pattern::NonTerm(consonant.clone())),
+            right: Some(Pattern::NonTerm(consonant)),
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["a", "p", "t", "s", "a"])),
+            word(&terms, &["a", "p", "i", "t", "i", "s", "a"])
+        );
+
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::Terms(Vec::new()),
+            replacement: replacement(&terms, &["a"]),
+            left: None,
+            right: None,
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["p", "t"])),
+            word(&terms, &["a", "p", "a", "t", "a"])
+        );
+        assert_eq!(apply_rule(&rule, &word(&terms, &[])), word(&terms, &["a"]));
+    }
+
+    #[test]
+    fn metathesis() {
+        let terms = terms_for_test();
+        let vowels = find_syms!(&terms, wrap, "a", "i").unwrap();
+        let vowel = Pattern::NonTerm(NonTerminal::new("V", vowels));
+
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::And(
+                Box::new(Pattern::Capture(
+                    CaptureName::Index(1),
+                    Box::new(terms_pat(&terms, &["t"])),
+                )),
+                Box::new(Pattern::Capture(
+                    CaptureName::Index(2),
+                    Box::new(vowel),
+                )),
+            ),
+            replacement: vec![
+                Replacement::Capture(CaptureName::Index(2)),
+                Replacement::Capture(CaptureName::Index(1)),
+            ],
+            left: None,
+            right: Some(terms_pat(&terms, &["s"])),
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["p", "t", "a", "s", "t", "i"])),
+            word(&terms, &["p", "a", "t", "s", "t", "i"])
+        );
+    }
+
+    #[test]
+    fn back_references() {
+        let terms = terms_for_test();
+        let consonants = find_syms!(&terms, wrap, "p", "t", "s").unwrap();
+        let consonant = Pattern::Capture(
+            CaptureName::Index(1),
+            Box::new(Pattern::NonTerm(NonTerminal::new("C", consonants))),
+        );
+
+        // Degemination: \C₁ > ∅ / _ \C₁
+        let degeminate = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: consonant.clone(),
+            replacement: Vec::new(),
+            left: None,
+            right: Some(consonant.clone()),
+        };
+
+        assert_eq!(
+            apply_rule(
+                &degeminate,
+                &word(&terms, &["a", "t", "t", "a", "p", "s"])
+            ),
+            word(&terms, &["a", "t", "a", "p", "s"])
+        );
+
+        // Gemination: ∅ > \C₁ / \C₁ _ a
+        let geminate = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::Terms(Vec::new()),
+            replacement: vec![Replacement::Capture(CaptureName::Index(1))],
+            left: Some(consonant),
+            right: Some(terms_pat(&terms, &["a"])),
+        };
+
+        assert_eq!(
+            apply_rule(&geminate, &word(&terms, &["a", "p", "a", "s", "i"])),
+            word(&terms, &["a", "p", "p", "a", "s", "i"])
+        );
+    }
+
+    #[test]
+    fn word_internal_class_env() {
+        let terms = terms_for_test();
+        let vowels = find_syms!(&terms, wrap, "a", "i").unwrap();
+        let vowel = NonTerminal::new("V", vowels);
+
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["p"]),
+            replacement: replacement(&terms, &["f"]),
+            left: Some(Pattern::NonTerm(vowel.clone())),
+            right: Some(Pattern::NonTerm(vowel)),
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["a", "p", "i", "p", "a", "p"])),
+            word(&terms, &["a", "f", "i", "f", "a", "p"])
+        );
+    }
+
+    #[test]
+    fn word_boundaries() {
+        let terms = terms_for_test();
+        let initial = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["p"]),
+            replacement: replacement(&terms, &["f"]),
+            left: Some(Pattern::Start),
+            right: None,
+        };
+        let last = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["t"]),
+            replacement: replacement(&terms, &["s"]),
+            left: None,
+            right: Some(Pattern::End),
+        };
+
+        assert_eq!(
+            apply_rule(&initial, &word(&terms, &["p", "a", "p"])),
+            word(&terms, &["f", "a", "p"])
+        );
+        assert_eq!(
+            apply_rule(&last, &word(&terms, &["t", "a", "t"])),
+            word(&terms, &["t", "a", "s"])
+        );
+    }
+
+    #[test]
+    fn direction() {
+        let terms = terms_for_test();
+        let ltr = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["a", "a"]),
+            replacement: replacement(&terms, &["t"]),
+            left: None,
+            right: None,
+        };
+        let rtl = Rule { direction: Direction::Rtl, ..ltr.clone() };
+        let input = word(&terms, &["a", "a", "a"]);
+
+        assert_eq!(apply_rule(&ltr, &input), word(&terms, &["t", "a"]));
+        assert_eq!(apply_rule(&rtl, &input), word(&terms, &["a", "t"]));
+    }
+
+    #[test]
+    fn feeding() {
+        let terms = terms_for_test();
+        let progressive = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["a"]),
+            replacement: replacement(&terms, &["i"]),
+            left: Some(terms_pat(&terms, &["i"])),
+            right: None,
+        };
+        let regressive = Rule {
+            left: None,
+            right: Some(terms_pat(&terms, &["i"])),
+            ..progressive.clone()
+        };
+        let with = |rule: &Rule, direction, feeding| Rule {
+            direction,
+            feeding,
+            ..rule.clone()
+        };
+        let harmonic = word(&terms, &["i", "i", "i", "i"]);
+
+        let input = word(&terms, &["i", "a", "a", "a"]);
+        let once = word(&terms, &["i", "i", "a", "a"]);
+        assert_eq!(apply_rule(&progressive, &input), once);
+        let rule = with(&progressive, Direction::Ltr, true);
+        assert_eq!(apply_rule(&rule, &input), harmonic);
+        let rule = with(&progressive, Direction::Rtl, true);
+        assert_eq!(apply_rule(&rule, &input), once);
+
+        let input = word(&terms, &["a", "a", "a", "i"]);
+        let once = word(&terms, &["a", "a", "i", "i"]);
+        assert_eq!(apply_rule(&regressive, &input), once);
+        let rule = with(&regressive, Direction::Ltr, true);
+        assert_eq!(apply_rule(&rule, &input), once);
+        let rule = with(&regressive, Direction::Rtl, true);
+        assert_eq!(apply_rule(&rule, &input), harmonic);
+        let rule = with(&regressive, Direction::Rtl, false);
+        assert_eq!(apply_rule(&rule, &input), once);
+    }
+
+    #[test]
+    fn feeding_own_output() {
+        let terms = terms_for_test();
+        let rule = Rule {
+            direction: Direction::Rtl,
+            feeding: true,
+            target: terms_pat(&terms, &["a", "t"]),
+            replacement: replacement(&terms, &["t", "a"]),
+            left: None,
+            right: None,
+        };
+        let input = word(&terms, &["a", "a", "t"]);
+        let output = word(&terms, &["a", "t", "a"]);
+
+        assert_eq!(apply_rule(&rule, &input), output);
+        let rule = Rule { direction: Direction::Ltr, ..rule };
+        assert_eq!(apply_rule(&rule, &input), output);
+    }
+
+    #[test]
+    fn feeding_insertion() {
+        let terms = terms_for_test();
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: true,
+            target: Pattern::Terms(Vec::new()),
+            replacement: replacement(&terms, &["a"]),
+            left: Some(terms_pat(&terms, &["t"])),
+            right: None,
+        };
+
+        assert_eq!(
+            apply_rule(&rule, &word(&terms, &["t", "t"])),
+            word(&terms, &["t", "a", "t", "a"])
+        );
+    }
+
+    #[test]
+    fn simultaneous_chain_shift() {
+        let terms = terms_for_test();
+        let shift = |from: &str, to: &str| Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &[from]),
+            replacement: replacement(&terms, &[to]),
+            left: None,
+            right: None,
+        };
+        let rules = vec![shift("a", "i"), shift("i", "s")];
+        let input = word(&terms, &["p", "a", "t", "i"]);
+
+        let block = Block::new(Mode::Simultaneous, rules.clone());
+        let mut errs = Diagnostic::new();
+        assert_eq!(
+            block.apply(&input, &mut errs),
+            word(&terms, &["p", "i", "t", "s"])
+        );
+
+        let block = Block::new(Mode::Sequential, rules);
+        assert_eq!(
+            block.apply(&input, &mut errs),
+            word(&terms, &["p", "s", "t", "s"])
+        );
+        assert!(errs.is_empty());
+    }
+
+    #[test]
+    fn iterative_fixed_point() {
+        let terms = terms_for_test();
+        // Each pass only moves `a` one position to the right.
+        let rule = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: terms_pat(&terms, &["a", "t"]),
+            replacement: replacement(&terms, &["t", "a"]),
+            left: None,
+            right: None,
+        };
+        let block = Block::new(Mode::Iterative, vec![rule]);
+        let mut errs = Diagnostic::new();
+
+        assert_eq!(
+            block.apply(&word(&terms, &["a", "t", "t", "t"]), &mut errs),
+            word(&terms, &["t", "t", "t", "a"])
+        );
+        assert!(errs.is_empty());
+    }
+
+    #[test]
+    fn iterative_cycle() {
+        let terms = terms_for_test();
+        let flip = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::NonTerm(NonTerminal::new(
+                "V",
+                find_syms!(&terms, wrap, "a", "i").unwrap(),
+            )),
+            replacement: vec![Replacement::Class(NonTerminal::new(
+                "W",
+                find_syms!(&terms, wrap, "i", "a").unwrap(),
+            ))],
+            left: None,
+            right: None,
+        };
+        let block = Block::new(Mode::Iterative, vec![flip]);
+        let mut errs = Diagnostic::new();
+
+        assert_eq!(
+            block.apply(&word(&terms, &["p", "a"]), &mut errs),
+            word(&terms, &["p", "a"])
+        );
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].warning);
+        assert!(matches!(errs[0].kind, ErrorKind::NoFixedPoint(_, 2)));
+    }
+
+    #[test]
+    fn iterative_cap() {
+        let terms = terms_for_test();
+        let grow = Rule {
+            direction: Direction::Ltr,
+            feeding: false,
+            target: Pattern::Terms(Vec::new()),
+            replacement: replacement(&terms, &["a"]),
+            left: None,
+            right: Some(Pattern::End),
+        };
+        let block = Block::new(Mode::Iterative, vec![grow]);
+        let mut errs = Diagnostic::new();
+
+        let output = block.apply(&word(&terms, &["p"]), &mut errs);
+        assert_eq!(output.symbols.len(), MAX_ITERATIONS + 1);
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(
+            errs[0].kind,
+            ErrorKind::NoFixedPoint(_, MAX_ITERATIONS)
+        ));
+    }
+
+    #[test]
+    fn rules_in_order() {
+        let terms = terms_for_test();
+        let engine = Engine::new(vec![
+            Rule {
+                direction: Direction::Ltr,
+                feeding: false,
+                target: terms_pat(&terms, &["p"]),
+                replacement: replacement(&terms, &["f"]),
+                left: None,
+                right: None,
+            },
+            Rule {
+                direction: Direction::Ltr,
+                feeding: false,
+                target: terms_pat(&terms, &["f"]),
+                replacement: replacement(&terms, &["s"]),
+                left: None,
+                right: None,
+            },
+        ]);
+
+        let mut errs = Diagnostic::new();
+        assert_eq!(
+            engine.apply(&word(&terms, &["p", "a", "f"]), &mut errs),
+            word(&terms, &["s", "a", "s"])
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     fmt_ext::SeqFmt,
+    segment::SegmentError,
     source::Span,
     token::{Token, TokenPattern},
 };
@@ -10,6 +11,28 @@ pub enum ErrorKind {
     BadChar(Span),
     UnclosedString(Span),
     Expected(String, Token),
+    UndefinedClass(Span),
+    DuplicateSymbol(Span, Span),
+    DuplicateClass(Span, Span),
+    CyclicClass(Vec<Span>),
+    Unsegmentable(Span, SegmentError),
+    BoundaryInTarget(Span),
+    BadRepetition(Span),
+    NotAClass(Span),
+    ArityMismatch(Span, usize, Span, usize),
+    BadIndex(Span),
+    UndefinedCapture(Span),
+    NoFixedPoint(String, usize),
+    UnknownFeature(Span),
+    NoSegment(Span, String),
+    DuplicateFeature(Span, Span),
+    ConflictingFeature(Span, String),
+    NotASegment(Span),
+    IgnoredModifier(Span),
+    ClassInFeature(Span),
+    NullSymbol(Span),
+    RepeatedCapture(Span),
+    UnalignedMatrix(Span),
 }
 
 impl ErrorKind {
@@ -46,6 +69,165 @@ impl fmt::Display for ErrorKind {
             ErrorKind::UnclosedString(span) => {
                 write!(fmtr, "unclosed string {}", span)
             },
+
+            ErrorKind::UndefinedClass(span) => {
+                write!(fmtr, "undefined class {} {}", span.content(), span)
+            },
+
+            ErrorKind::DuplicateSymbol(span, prev) => write!(
+                fmtr,
+                "duplicate alphabet symbol {} {}, previously declared {}",
+                span.content(),
+                span,
+                prev
+            ),
+
+            ErrorKind::DuplicateClass(span, prev) => write!(
+                fmtr,
+                "duplicate class {} {}, previously defined {}",
+                span.content(),
+                span,
+                prev
+            ),
+
+            ErrorKind::CyclicClass(spans) => {
+                fmtr.write_str("cyclic class definition:")?;
+                for span in spans {
+                    write!(fmtr, "\n    {} {}", span.content(), span)?;
+                }
+                Ok(())
+            },
+
+            ErrorKind::BoundaryInTarget(span) => write!(
+                fmtr,
+                "word boundary is only allowed in the environment {}",
+                span
+            ),
+
+            ErrorKind::BadRepetition(span) => write!(
+                fmtr,
+                "invalid repetition bounds {} {}",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::NotAClass(span) => write!(
+                fmtr,
+                "expected a class expression, found {} {}",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::ArityMismatch(span, arity, target, expected) => write!(
+                fmtr,
+                "replacement class {} {} has {} members, but target {} {} \
+                 has {}",
+                span.content(),
+                span,
+                arity,
+                target.content(),
+                target,
+                expected
+            ),
+
+            ErrorKind::BadIndex(span) => {
+                write!(fmtr, "invalid index {} {}", span.content(), span)
+            },
+
+            ErrorKind::UndefinedCapture(span) => write!(
+                fmtr,
+                "capture {} {} does not refer to an element of the rule",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::NoFixedPoint(word, iterations) => write!(
+                fmtr,
+                "iterative rules did not reach a fixed point for `{}` after {} \
+                 iterations",
+                word, iterations
+            ),
+
+            ErrorKind::UnknownFeature(span) => {
+                write!(fmtr, "unknown feature {} {}", span.content(), span)
+            },
+
+            ErrorKind::NoSegment(span, desc) => write!(
+                fmtr,
+                "no segment of the alphabet changes {} by {} {}, so it is \
+                 left unchanged",
+                desc,
+                span.content(),
+                span
+            ),
+
+            ErrorKind::DuplicateFeature(span, prev) => write!(
+                fmtr,
+                "duplicate feature {} {}, previously declared {}",
+                span.content(),
+                span,
+                prev
+            ),
+
+            ErrorKind::ConflictingFeature(span, desc) => write!(
+                fmtr,
+                "feature value {} {} is given to {}, which already has a \
+                 value",
+                span.content(),
+                span,
+                desc
+            ),
+
+            ErrorKind::NotASegment(span) => write!(
+                fmtr,
+                "feature values can only be given to single segments, found \
+                 {} {}",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::IgnoredModifier(span) => write!(
+                fmtr,
+                "rule modifier {} {} has no effect inside a simultaneous \
+                 block",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::ClassInFeature(span) => write!(
+                fmtr,
+                "feature values cannot refer to classes, found {} {}",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::NullSymbol(span) => write!(
+                fmtr,
+                "alphabet symbol {} {} is the null symbol when it makes up a \
+                 whole side of a rule, write it as '0' there",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::RepeatedCapture(span) => write!(
+                fmtr,
+                "capture {} {} cannot be repeated, since every repetition \
+                 would have to match the first one",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::UnalignedMatrix(span) => write!(
+                fmtr,
+                "feature matrix {} {} has no single segment of the target in \
+                 the same position to change",
+                span.content(),
+                span
+            ),
+
+            ErrorKind::Unsegmentable(span, err) => {
+                write!(fmtr, "{} {}", err, span)
+            },
         }
     }
 }
@@ -97,6 +279,12 @@ impl fmt::Display for Diagnostic {
     }
 }
 
+impl Default for Diagnostic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Diagnostic {
     pub fn new() -> Self {
         Self { errors: Vec::new() }
@@ -110,6 +298,14 @@ impl Diagnostic {
         self.errors.push(Error { kind, warning: true });
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|error| !error.warning)
+    }
+
     pub fn as_slice(&self) -> &[Error] {
         &self.errors
     }
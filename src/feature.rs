@@ -0,0 +1,426 @@
+use crate::{
+    ast::FeatureSpec,
+    error::{Diagnostic, ErrorKind},
+    ipa::{self, Feature},
+    symbol::{DescKey, Symbol, Table, Terminal},
+};
+use std::{fmt, rc::Rc};
+
+// A feature declared in a rule file. Binary features have the values `+` and
+// `-`, and a segment which is not listed has no value at all.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeatureDef {
+    name: String,
+    values: Vec<(String, Vec<Terminal>)>,
+}
+
+impl FeatureDef {
+    pub fn new<S, V>(name: S, values: V) -> Self
+    where
+        S: Into<String>,
+        V: Into<Vec<(String, Vec<Terminal>)>>,
+    {
+        Self { name: name.into(), values: values.into() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn values(&self) -> &[(String, Vec<Terminal>)] {
+        &self.values
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.values.iter().all(|(value, _)| value == "+" || value == "-")
+    }
+
+    pub fn value_of(&self, term: &Terminal) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(_, members)| members.contains(term))
+            .map(|(value, _)| &**value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeatureRef {
+    Ipa(Feature),
+    // A declared feature overrides the built-in feature of the same name, which
+    // still applies to the segments the declaration leaves out.
+    Declared { def: Rc<FeatureDef>, value: String, fallback: Option<Feature> },
+}
+
+impl FeatureRef {
+    pub fn name(&self) -> &str {
+        match self {
+            FeatureRef::Ipa(feature) => feature.name(),
+            FeatureRef::Declared { def, .. } if def.is_binary() => def.name(),
+            FeatureRef::Declared { value, .. } => value,
+        }
+    }
+
+    pub fn test(&self, term: &Terminal) -> Option<bool> {
+        match self {
+            FeatureRef::Ipa(feature) => ipa::features(term.desc())
+                .map(|features| features.has(*feature)),
+            FeatureRef::Declared { def, value, fallback } => {
+                match def.value_of(term) {
+                    Some(other) => Some(other == value),
+                    None => fallback.and_then(|feature| {
+                        FeatureRef::Ipa(feature).test(term)
+                    }),
+                }
+            },
+        }
+    }
+
+    fn ipa(&self) -> Option<Feature> {
+        match self {
+            FeatureRef::Ipa(feature) => Some(*feature),
+            FeatureRef::Declared { fallback, .. } => *fallback,
+        }
+    }
+
+    fn declares(&self, def: &Rc<FeatureDef>) -> bool {
+        match self {
+            FeatureRef::Declared { def: other, .. } => Rc::ptr_eq(other, def),
+            FeatureRef::Ipa(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for FeatureRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureSystem {
+    defs: Vec<Rc<FeatureDef>>,
+}
+
+impl FeatureSystem {
+    pub fn new<V>(defs: V) -> Self
+    where
+        V: Into<Vec<Rc<FeatureDef>>>,
+    {
+        Self { defs: defs.into() }
+    }
+
+    pub fn defs(&self) -> &[Rc<FeatureDef>] {
+        &self.defs
+    }
+
+    // Binary features are named after the declaration, the others after their
+    // values, as in `[+voice]` and `[+high]`.
+    pub fn find(&self, name: &str) -> Option<FeatureRef> {
+        let fallback = Feature::from_name(name);
+
+        for def in &self.defs {
+            let value = if def.is_binary() && def.name() == name {
+                "+"
+            } else if def.values().iter().any(|(value, _)| value == name) {
+                name
+            } else {
+                continue;
+            };
+            return Some(FeatureRef::Declared {
+                def: def.clone(),
+                value: value.to_owned(),
+                fallback,
+            });
+        }
+
+        fallback.map(FeatureRef::Ipa)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Matrix {
+    specs: Vec<(FeatureRef, bool)>,
+}
+
+impl Matrix {
+    pub fn new<V>(specs: V) -> Self
+    where
+        V: Into<Vec<(FeatureRef, bool)>>,
+    {
+        Self { specs: specs.into() }
+    }
+
+    #[allow(clippy::result_unit_err)]
+    pub fn compile(
+        specs: &[FeatureSpec],
+        features: &FeatureSystem,
+        errs: &mut Diagnostic,
+    ) -> Result<Self, ()> {
+        let mut compiled = Vec::with_capacity(specs.len());
+        let mut failed = false;
+
+        for spec in specs {
+            match features.find(&spec.name.content) {
+                Some(feature) => compiled.push((feature, spec.value)),
+                None => {
+                    errs.raise(ErrorKind::UnknownFeature(
+                        spec.name.span.clone(),
+                    ));
+                    failed = true;
+                },
+            }
+        }
+
+        if failed {
+            Err(())
+        } else {
+            Ok(Self::new(compiled))
+        }
+    }
+
+    pub fn specs(&self) -> &[(FeatureRef, bool)] {
+        &self.specs
+    }
+
+    // Segments without a value for some feature of the matrix, such as
+    // terminals outside of the IPA inventory, never satisfy it.
+    pub fn matches(&self, term: &Terminal) -> bool {
+        self.specs
+            .iter()
+            .all(|(feature, value)| feature.test(term) == Some(*value))
+    }
+
+    // Finds the segment of the alphabet which satisfies the matrix and
+    // otherwise keeps the features of `term`, preferring the fewest changes.
+    pub fn change<'alpha>(
+        &self,
+        term: &Terminal,
+        alphabet: &'alpha Table<Terminal>,
+        features: &FeatureSystem,
+    ) -> Option<&'alpha Terminal> {
+        let source = ipa::features(term.desc());
+        let changed =
+            self.specs.iter().filter_map(|(feature, _)| feature.ipa());
+
+        alphabet
+            .as_slice()
+            .iter()
+            .filter(|other| self.matches(other))
+            .filter_map(|other| {
+                let builtin = match (&source, ipa::features(other.desc())) {
+                    (Some(source), Some(target)) => {
+                        source.distance(&target, changed.clone())?
+                    },
+                    (None, None) => 0,
+                    _ => return None,
+                };
+                let declared = self.declared_distance(term, other, features)?;
+                Some((builtin + declared, other))
+            })
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, other)| other)
+    }
+
+    fn declared_distance(
+        &self,
+        term: &Terminal,
+        other: &Terminal,
+        features: &FeatureSystem,
+    ) -> Option<u32> {
+        let mut distance = 0;
+
+        for def in features.defs() {
+            match (def.value_of(term), def.value_of(other)) {
+                (Some(source), Some(target)) if source != target => {
+                    if !self
+                        .specs
+                        .iter()
+                        .any(|(feature, _)| feature.declares(def))
+                    {
+                        return None;
+                    }
+                    distance += 1;
+                },
+                _ => (),
+            }
+        }
+
+        Some(distance)
+    }
+
+    pub fn members(&self, alphabet: &Table<Terminal>) -> Vec<Symbol> {
+        alphabet
+            .as_slice()
+            .iter()
+            .filter(|term| self.matches(term))
+            .map(|term| Symbol::Term(term.clone()))
+            .collect()
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("[")?;
+        for (i, (feature, value)) in self.specs.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str(" ")?;
+            }
+            let sign = if *value { '+' } else { '-' };
+            write!(fmt, "{}{}", sign, feature)?;
+        }
+        fmt.write_str("]")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FeatureDef, FeatureRef, FeatureSystem, Matrix};
+    use crate::{
+        ast::{ClassExprKind, Decl, FeatureSpec},
+        error::{Diagnostic, ErrorKind},
+        ipa::{Feature, Manner},
+        make_terms,
+        parser::Parser,
+        source::Src,
+        symbol::DescKey,
+    };
+    use std::rc::Rc;
+
+    fn parse_specs(source: &str, errs: &mut Diagnostic) -> Vec<FeatureSpec> {
+        let src = Src::new("foo.psh", format!("class \\X = {}", source));
+        let mut parser = Parser::new(src.reader(), errs);
+        let program = parser.parse_program(errs).unwrap();
+        match &program.decls[0] {
+            Decl::Class(decl) => match &decl.expr.kind {
+                ClassExprKind::Matrix(specs) => specs.clone(),
+                expr => panic!("expected matrix, found {:?}", expr),
+            },
+            decl => panic!("expected class, found {:?}", decl),
+        }
+    }
+
+    #[test]
+    fn voiceless_stops() {
+        // `T` is not an IPA segment, so it has no features at all.
+        let terms = make_terms!("a", "b", "p", "t", "s", "T");
+        let matrix = Matrix::new(vec![
+            (FeatureRef::Ipa(Feature::Manner(Manner::Stop)), true),
+            (FeatureRef::Ipa(Feature::Voice), false),
+        ]);
+
+        let members = matrix.members(&terms);
+        let descs = members.iter().map(DescKey::desc).collect::<Vec<_>>();
+        assert_eq!(descs, ["p", "t"]);
+        assert_eq!(matrix.to_string(), "[+stop -voice]");
+    }
+
+    #[test]
+    fn unknown_features() {
+        let mut errs = Diagnostic::new();
+        let specs = parse_specs("[+stop -vioce +nsal]", &mut errs);
+        let features = FeatureSystem::default();
+        assert!(Matrix::compile(&specs, &features, &mut errs).is_err());
+
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 2);
+        match &errs[0].kind {
+            ErrorKind::UnknownFeature(span) => {
+                assert_eq!(span.as_str(), "vioce")
+            },
+            kind => panic!("expected unknown feature, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn feature_change() {
+        let terms = make_terms!("a", "b", "d", "p", "t", "ɸ", "β", "s", "z");
+        let fricative = Matrix::new(vec![(
+            FeatureRef::Ipa(Feature::Manner(Manner::Fricative)),
+            true,
+        )]);
+        let features = FeatureSystem::default();
+        let change = |matrix: &Matrix, desc| {
+            let term = terms.find(desc).unwrap();
+            matrix
+                .change(term, &terms, &features)
+                .map(|term| term.desc().to_owned())
+        };
+
+        assert_eq!(change(&fricative, "p").as_deref(), Some("ɸ"));
+        assert_eq!(change(&fricative, "d").as_deref(), Some("z"));
+        assert_eq!(change(&fricative, "s").as_deref(), Some("s"));
+        assert_eq!(change(&fricative, "a"), None);
+
+        let voiced = Matrix::new(vec![(FeatureRef::Ipa(Feature::Voice), true)]);
+        assert_eq!(change(&voiced, "t").as_deref(), Some("d"));
+        assert_eq!(change(&voiced, "ɸ").as_deref(), Some("β"));
+    }
+
+    #[test]
+    fn declared_features() {
+        let terms = make_terms!("P", "B", "T", "D", "a", "b", "p");
+        let def = |name: &str, values: Vec<(&str, Vec<&str>)>| {
+            let values = values
+                .into_iter()
+                .map(|(value, descs)| {
+                    let members = descs
+                        .into_iter()
+                        .map(|desc| terms.find(desc).unwrap().clone())
+                        .collect::<Vec<_>>();
+                    (value.to_owned(), members)
+                })
+                .collect::<Vec<_>>();
+            Rc::new(FeatureDef::new(name, values))
+        };
+        let features = FeatureSystem::new(vec![
+            def("voice", vec![("+", vec!["B", "D"]), ("-", vec!["P", "T"])]),
+            def(
+                "place",
+                vec![("labial", vec!["P", "B"]), ("coronal", vec!["T", "D"])],
+            ),
+        ]);
+
+        let find = |name: &str| features.find(name).unwrap();
+        let matrix = |specs: Vec<(&str, bool)>| {
+            Matrix::new(
+                specs
+                    .into_iter()
+                    .map(|(name, value)| (find(name), value))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let descs = |matrix: &Matrix| {
+            let members = matrix.members(&terms);
+            members.iter().map(|sym| sym.desc().to_owned()).collect::<Vec<_>>()
+        };
+
+        // `voice` is declared, but still falls back to the IPA feature for
+        // segments the declaration leaves out.
+        assert_eq!(descs(&matrix(vec![("voice", true)])), ["B", "D", "a", "b"]);
+        assert_eq!(descs(&matrix(vec![("coronal", true)])), ["D", "T"]);
+        assert_eq!(
+            descs(&matrix(vec![("labial", true), ("voice", false)])),
+            ["P", "p"]
+        );
+        assert!(matches!(find("stop"), FeatureRef::Ipa(_)));
+        assert!(features.find("fortis").is_none());
+
+        let change = |matrix: &Matrix, desc| {
+            let term = terms.find(desc).unwrap();
+            matrix
+                .change(term, &terms, &features)
+                .map(|term| term.desc().to_owned())
+        };
+        let voiced = matrix(vec![("voice", true)]);
+        assert_eq!(change(&voiced, "P").as_deref(), Some("B"));
+        assert_eq!(change(&voiced, "T").as_deref(), Some("D"));
+        assert_eq!(change(&voiced, "p").as_deref(), Some("b"));
+        let coronal = matrix(vec![("coronal", true)]);
+        assert_eq!(change(&coronal, "B").as_deref(), Some("D"));
+
+        let voice = &features.defs()[0];
+        assert_eq!(voice.value_of(terms.find("B").unwrap()), Some("+"));
+        assert!(voice.is_binary());
+        assert!(!features.defs()[1].is_binary());
+    }
+}
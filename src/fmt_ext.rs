@@ -36,7 +36,7 @@ impl<'buf> SeqFmt<'buf> {
     pub fn finish(self) -> fmt::Result {
         let (first, second) = self.cache.split_at(self.curr);
 
-        if first.len() > 0 {
+        if !first.is_empty() {
             let str = str::from_utf8(first).unwrap();
             self.buf.write_str(str)?;
             self.buf.write_str(" or ")?;
@@ -58,8 +58,8 @@ impl<'buf> fmt::Debug for SeqFmt<'buf> {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmtr,
-            "SeqFmt {} buf: {:?}, cache: {:?}, curr: {} {}",
-            '{', self.buf as *const _, self.cache, self.curr, '}'
+            "SeqFmt {{ buf: {:?}, cache: {:?}, curr: {} }}",
+            self.buf as *const _, self.cache, self.curr
         )
     }
 }
@@ -1,392 +1,768 @@
-pub const SYMBOLS: &[&str] = &[
-    "a",
-    "a",
-    "b",
-    "b",
-    "b̪",
-    "b̪",
-    "b̪v",
-    "bβ",
-    "c",
-    "c",
-    "cç",
-    "cʎ̝̊",
-    "cʎ̝̊ʼ",
-    "cʼ",
-    "cʼ",
-    "d",
-    "d",
-    "dz",
-    "dɮ",
-    "dɹ̝",
-    "dʑ",
-    "d̠ɹ̠˔",
-    "d̠ʒ",
-    "d̪",
-    "d̪ð",
-    "d̼",
-    "e",
-    "e",
-    "e̞",
-    "f",
-    "f",
-    "fʼ",
-    "h",
-    "h",
-    "h̃",
-    "i",
-    "i",
-    "j",
-    "j",
-    "j̃",
-    "j̊",
-    "k",
-    "k",
-    "kx",
-    "kxʼ",
-    "kʟ̝̊",
-    "kʟ̝̊ʼ",
-    "kʼ",
-    "kʼ",
-    "k͡p",
-    "k͡p",
-    "l",
-    "l",
-    "l̥",
-    "l̥",
-    "m",
-    "m",
-    "m̥",
-    "m̥",
-    "n",
-    "n",
-    "n̥",
-    "n̥",
-    "n̪",
-    "n̼",
-    "o",
-    "o",
-    "o̞",
-    "p",
-    "p",
-    "pɸ",
-    "pʼ",
-    "pʼ",
-    "p̪",
-    "p̪",
-    "p̪f",
-    "q",
-    "q",
-    "qʼ",
-    "qʼ",
-    "qχ",
-    "qχʼ",
-    "r",
-    "r",
-    "r̥",
-    "r̥",
-    "s",
-    "s",
-    "sʼ",
-    "sʼ",
-    "t",
-    "t",
-    "ts",
-    "tsʼ",
-    "tɕ",
-    "tɬ",
-    "tɬʼ",
-    "tɹ̝̊",
-    "tʼ",
-    "tʼ",
-    "t̠ɹ̠̊˔",
-    "t̠ʃ",
-    "t̠ʃʼ",
-    "t̪",
-    "t̪ʼ",
-    "t̪θ",
-    "t̪θʼ",
-    "t̼",
-    "u",
-    "u",
-    "v",
-    "v",
-    "w",
-    "w",
-    "w̃",
-    "x",
-    "x",
-    "xʼ",
-    "y",
-    "y",
-    "z",
-    "z",
-    "ä",
-    "æ",
-    "æ",
-    "ç",
-    "ç",
-    "ð",
-    "ð",
-    "ð̞",
-    "ð̠",
-    "ð̼",
-    "ø",
-    "ø",
-    "ø̞",
-    "ħ",
-    "ħ",
-    "ŋ",
-    "ŋ",
-    "ŋ̊",
-    "ŋ͡m",
-    "ŋ͡m",
-    "œ",
-    "œ",
-    "ǀ",
-    "ǀ",
-    "ǀ̃",
-    "ǀ̬",
-    "ǁ",
-    "ǁ",
-    "ǁ̬",
-    "ǂ",
-    "ǂ",
-    "ǂ̃",
-    "ǂ̬",
-    "ǃ",
-    "ǃ",
-    "ǃ",
-    "ǃ̃",
-    "ǃ̬",
-    "ȡ",
-    "ȴ",
-    "ȵ",
-    "ȶ",
-    "ɐ",
-    "ɐ",
-    "ɑ",
-    "ɑ",
-    "ɒ",
-    "ɒ",
-    "ɒ̈",
-    "ɓ",
-    "ɓ",
-    "ɓ̥",
-    "ɔ",
-    "ɔ",
-    "ɕ",
-    "ɕ",
-    "ɕʼ",
-    "ɖ",
-    "ɖ",
-    "ɖʐ",
-    "ɗ",
-    "ɗ",
-    "ɗ̥",
-    "ɗ̪",
-    "ɘ",
-    "ɘ",
-    "ə",
-    "ə",
-    "ɛ",
-    "ɛ",
-    "ɜ",
-    "ɜ",
-    "ɞ",
-    "ɞ",
-    "ɟ",
-    "ɟ",
-    "ɟʝ",
-    "ɠ",
-    "ɠ",
-    "ɠ̊",
-    "ɡ",
-    "ɡ",
-    "ɡɣ",
-    "ɡʟ̝",
-    "ɡ͡b",
-    "ɡ͡b",
-    "ɢ",
-    "ɢ",
-    "ɢ̆",
-    "ɣ",
-    "ɣ",
-    "ɤ",
-    "ɤ",
-    "ɤ̞",
-    "ɥ",
-    "ɥ",
-    "ɥ̊",
-    "ɦ",
-    "ɦ",
-    "ɧ",
-    "ɨ",
-    "ɨ",
-    "ɨ̞",
-    "ɪ",
-    "ɪ",
-    "ɪ̈",
-    "ɫ",
-    "ɬ",
-    "ɬ",
-    "ɬʼ",
-    "ɬʼ",
-    "ɭ",
-    "ɭ",
-    "ɭ˔",
-    "ɭ̆",
-    "ɭ̊",
-    "ɭ̊˔",
-    "ɮ",
-    "ɮ",
-    "ɯ",
-    "ɯ",
-    "ɯ̞",
-    "ɰ",
-    "ɰ",
-    "ɰ̊",
-    "ɱ",
-    "ɱ",
-    "ɲ",
-    "ɲ",
-    "ɲ̊",
-    "ɳ",
-    "ɳ",
-    "ɳ̊",
-    "ɳ̊",
-    "ɴ",
-    "ɴ",
-    "ɵ",
-    "ɵ",
-    "ɶ",
-    "ɶ",
-    "ɸ",
-    "ɸ",
-    "ɸʼ",
-    "ɹ",
-    "ɹ",
-    "ɹ̠˔",
-    "ɹ̠̊˔",
-    "ɹ̥",
-    "ɺ",
-    "ɺ",
-    "ɻ",
-    "ɻ",
-    "ɻ˔",
-    "ɻ̊",
-    "ɽ",
-    "ɽ",
-    "ɽr",
-    "ɽ̊",
-    "ɽ̊r̥",
-    "ɾ",
-    "ɾ",
-    "ɾ̥",
-    "ɾ̼",
-    "ʀ",
-    "ʀ",
-    "ʀ̥",
-    "ʁ",
-    "ʁ",
-    "ʂ",
-    "ʂ",
-    "ʂʼ",
-    "ʃ",
-    "ʃ",
-    "ʃʼ",
-    "ʄ",
-    "ʄ",
-    "ʄ̊",
-    "ʈ",
-    "ʈ",
-    "ʈɭ̊˔",
-    "ʈʂ",
-    "ʈʂʼ",
-    "ʈʼ",
-    "ʈʼ",
-    "ʉ",
-    "ʉ",
-    "ʉ̞",
-    "ʊ",
-    "ʊ̈",
-    "ʋ",
-    "ʋ",
-    "ʋ̥",
-    "ʌ",
-    "ʌ",
-    "ʍ",
-    "ʍ",
-    "ʎ",
-    "ʎ",
-    "ʎ̆",
-    "ʎ̝",
-    "ʎ̝̊",
-    "ʎ̥",
-    "ʏ",
-    "ʏ",
-    "ʐ",
-    "ʐ",
-    "ʑ",
-    "ʑ",
-    "ʒ",
-    "ʒ",
-    "ʔ",
-    "ʔ",
-    "ʔh",
-    "ʔ̞",
-    "ʕ",
-    "ʕ",
-    "ʘ",
-    "ʘ",
-    "ʘ̃",
-    "ʘ̬",
-    "ʙ",
-    "ʙ",
-    "ʙ̥",
-    "ʛ",
-    "ʛ",
-    "ʛ̥",
-    "ʜ",
-    "ʜ",
-    "ʝ",
-    "ʝ",
-    "ʞ",
-    "ʟ",
-    "ʟ",
-    "ʟ̆",
-    "ʟ̝",
-    "ʟ̝̊",
-    "ʟ̠",
-    "ʟ̥",
-    "ʡ",
-    "ʡ",
-    "ʡʢ",
-    "ʡʼ",
-    "ʡ̆",
-    "ʢ",
-    "ʢ",
-    "ʬ",
-    "ʭ",
-    "β",
-    "β",
-    "β̞",
-    "θ",
-    "θ",
-    "θʼ",
-    "θ̠",
-    "θ̼",
-    "χ",
-    "χ",
-    "χʼ",
-    "ᶑ",
-    "ᶑ̊",
-    "‼",
-    "‼̃",
-    "‼̬",
-    "ⱱ",
-    "ⱱ",
-    "ⱱ̟",
-    "ⱱ̟",
-    "ꞎ",
+use std::fmt;
+
+// The section of the IPA chart a symbol belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    Pulmonic,
+    NonPulmonic,
+    // Other symbols, affricates and double articulations.
+    Other,
+    Vowel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub symbol: &'static str,
+    pub category: Category,
+}
+
+// Generated by the build script from `data/ipa.tsv`, sorted and in NFC.
+include!(concat!(env!("OUT_DIR"), "/ipa.rs"));
+
+pub fn lookup(symbol: &str) -> Option<&'static Entry> {
+    SYMBOLS
+        .binary_search_by(|entry| entry.symbol.cmp(symbol))
+        .ok()
+        .map(|index| &SYMBOLS[index])
+}
+
+pub fn is_symbol(symbol: &str) -> bool {
+    lookup(symbol).is_some()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Place {
+    Bilabial,
+    Labiodental,
+    Linguolabial,
+    Dental,
+    Alveolar,
+    Postalveolar,
+    Retroflex,
+    AlveoloPalatal,
+    Palatal,
+    LabialPalatal,
+    Velar,
+    LabialVelar,
+    Uvular,
+    Pharyngeal,
+    Epiglottal,
+    Glottal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Manner {
+    Stop,
+    Affricate,
+    Fricative,
+    Nasal,
+    Trill,
+    Tap,
+    Approximant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Height {
+    Close,
+    NearClose,
+    CloseMid,
+    Mid,
+    OpenMid,
+    NearOpen,
+    Open,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Backness {
+    Front,
+    Central,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Airstream {
+    Pulmonic,
+    Ejective,
+    Implosive,
+    Click,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    Syllabic,
+    Consonantal,
+    Sonorant,
+    Continuant,
+    Voice,
+    Nasal,
+    Lateral,
+    Round,
+    Ejective,
+    Implosive,
+    Click,
+    Labial,
+    Coronal,
+    Dorsal,
+    High,
+    Mid,
+    Low,
+    Place(Place),
+    Manner(Manner),
+    Backness(Backness),
+}
+
+const FEATURE_NAMES: &[(&str, Feature)] = &[
+    ("syllabic", Feature::Syllabic),
+    ("consonantal", Feature::Consonantal),
+    ("sonorant", Feature::Sonorant),
+    ("continuant", Feature::Continuant),
+    ("voice", Feature::Voice),
+    ("nasal", Feature::Nasal),
+    ("lateral", Feature::Lateral),
+    ("round", Feature::Round),
+    ("ejective", Feature::Ejective),
+    ("implosive", Feature::Implosive),
+    ("click", Feature::Click),
+    ("labial", Feature::Labial),
+    ("coronal", Feature::Coronal),
+    ("dorsal", Feature::Dorsal),
+    ("high", Feature::High),
+    ("mid", Feature::Mid),
+    ("low", Feature::Low),
+    ("bilabial", Feature::Place(Place::Bilabial)),
+    ("labiodental", Feature::Place(Place::Labiodental)),
+    ("linguolabial", Feature::Place(Place::Linguolabial)),
+    ("dental", Feature::Place(Place::Dental)),
+    ("alveolar", Feature::Place(Place::Alveolar)),
+    ("postalveolar", Feature::Place(Place::Postalveolar)),
+    ("retroflex", Feature::Place(Place::Retroflex)),
+    ("alveolopalatal", Feature::Place(Place::AlveoloPalatal)),
+    ("palatal", Feature::Place(Place::Palatal)),
+    ("velar", Feature::Place(Place::Velar)),
+    ("uvular", Feature::Place(Place::Uvular)),
+    ("pharyngeal", Feature::Place(Place::Pharyngeal)),
+    ("epiglottal", Feature::Place(Place::Epiglottal)),
+    ("glottal", Feature::Place(Place::Glottal)),
+    ("stop", Feature::Manner(Manner::Stop)),
+    ("affricate", Feature::Manner(Manner::Affricate)),
+    ("fricative", Feature::Manner(Manner::Fricative)),
+    ("trill", Feature::Manner(Manner::Trill)),
+    ("tap", Feature::Manner(Manner::Tap)),
+    ("approximant", Feature::Manner(Manner::Approximant)),
+    ("front", Feature::Backness(Backness::Front)),
+    ("central", Feature::Backness(Backness::Central)),
+    ("back", Feature::Backness(Backness::Back)),
 ];
+
+const SYLLABIC: u16 = 1 << 0;
+const VOICE: u16 = 1 << 1;
+const NASAL: u16 = 1 << 2;
+const LATERAL: u16 = 1 << 3;
+const ROUND: u16 = 1 << 4;
+const AIRSTREAM: u16 = 1 << 5;
+const PLACE: u16 = 1 << 6;
+const MANNER: u16 = 1 << 7;
+const HEIGHT: u16 = 1 << 8;
+const BACKNESS: u16 = 1 << 9;
+
+impl Feature {
+    pub fn from_name(name: &str) -> Option<Self> {
+        FEATURE_NAMES
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|&(_, feature)| feature)
+    }
+
+    pub fn name(self) -> &'static str {
+        FEATURE_NAMES
+            .iter()
+            .find(|(_, other)| *other == self)
+            .map_or("?", |&(name, _)| name)
+    }
+}
+
+impl Feature {
+    // The fields of a bundle which may change when this feature is set.
+    fn fields(self) -> u16 {
+        match self {
+            Feature::Syllabic => SYLLABIC | PLACE | MANNER | HEIGHT | BACKNESS,
+            Feature::Consonantal => PLACE | MANNER,
+            Feature::Sonorant | Feature::Nasal => MANNER | NASAL,
+            Feature::Continuant | Feature::Manner(_) => MANNER,
+            Feature::Voice => VOICE,
+            Feature::Lateral => LATERAL,
+            Feature::Round => ROUND,
+            Feature::Ejective | Feature::Implosive | Feature::Click => {
+                AIRSTREAM
+            },
+            Feature::Labial
+            | Feature::Coronal
+            | Feature::Dorsal
+            | Feature::Place(_) => PLACE,
+            Feature::High | Feature::Mid | Feature::Low => HEIGHT,
+            Feature::Backness(_) => BACKNESS,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Features {
+    pub syllabic: bool,
+    pub voice: bool,
+    pub nasal: bool,
+    pub lateral: bool,
+    pub round: bool,
+    pub airstream: Airstream,
+    pub place: Option<Place>,
+    pub manner: Option<Manner>,
+    pub height: Option<Height>,
+    pub backness: Option<Backness>,
+}
+
+impl Features {
+    const fn consonant(place: Place, manner: Manner, voice: bool) -> Self {
+        Self {
+            syllabic: false,
+            voice,
+            nasal: matches!(manner, Manner::Nasal),
+            lateral: false,
+            round: false,
+            airstream: Airstream::Pulmonic,
+            place: Some(place),
+            manner: Some(manner),
+            height: None,
+            backness: None,
+        }
+    }
+
+    const fn vowel(height: Height, backness: Backness, round: bool) -> Self {
+        Self {
+            syllabic: true,
+            voice: true,
+            nasal: false,
+            lateral: false,
+            round,
+            airstream: Airstream::Pulmonic,
+            place: None,
+            manner: None,
+            height: Some(height),
+            backness: Some(backness),
+        }
+    }
+
+    const fn lateral(self) -> Self {
+        Self { lateral: true, ..self }
+    }
+
+    const fn round(self) -> Self {
+        Self { round: true, ..self }
+    }
+
+    const fn airstream(self, airstream: Airstream) -> Self {
+        Self { airstream, ..self }
+    }
+
+    // Glides, glottals and vowels are the non-consonantal segments; rhotic
+    // approximants still count as consonantal liquids.
+    pub fn consonantal(&self) -> bool {
+        match (self.place, self.manner) {
+            (None, _) | (Some(Place::Glottal), _) => false,
+            (Some(place), Some(Manner::Approximant)) => {
+                self.lateral
+                    || matches!(
+                        place,
+                        Place::Alveolar
+                            | Place::Postalveolar
+                            | Place::Retroflex
+                    )
+            },
+            _ => true,
+        }
+    }
+
+    pub fn has(&self, feature: Feature) -> bool {
+        use self::Place::*;
+
+        match feature {
+            Feature::Syllabic => self.syllabic,
+            Feature::Consonantal => self.consonantal(),
+            Feature::Sonorant => self.sonorant(),
+            Feature::Continuant => match self.manner {
+                None => true,
+                Some(manner) => matches!(
+                    manner,
+                    Manner::Fricative | Manner::Trill | Manner::Approximant
+                ),
+            },
+            Feature::Voice => self.voice,
+            Feature::Nasal => self.nasal,
+            Feature::Lateral => self.lateral,
+            Feature::Round => self.round,
+            Feature::Ejective => self.airstream == Airstream::Ejective,
+            Feature::Implosive => self.airstream == Airstream::Implosive,
+            Feature::Click => self.airstream == Airstream::Click,
+            Feature::Labial => matches!(
+                self.place,
+                Some(Bilabial | Labiodental | Linguolabial)
+                    | Some(LabialPalatal | LabialVelar)
+            ),
+            Feature::Coronal => matches!(
+                self.place,
+                Some(Linguolabial | Dental | Alveolar | Postalveolar)
+                    | Some(Retroflex | AlveoloPalatal)
+            ),
+            Feature::Dorsal => matches!(
+                self.place,
+                Some(Palatal | LabialPalatal | Velar | LabialVelar | Uvular)
+            ),
+            Feature::High => {
+                matches!(self.height, Some(Height::Close | Height::NearClose))
+            },
+            Feature::Mid => matches!(
+                self.height,
+                Some(Height::CloseMid | Height::Mid | Height::OpenMid)
+            ),
+            Feature::Low => {
+                matches!(self.height, Some(Height::NearOpen | Height::Open))
+            },
+            Feature::Place(place) => self.place == Some(place),
+            Feature::Manner(manner) => self.manner == Some(manner),
+            Feature::Backness(backness) => self.backness == Some(backness),
+        }
+    }
+
+    fn differences(&self, other: &Features) -> u16 {
+        let fields = [
+            (self.syllabic == other.syllabic, SYLLABIC),
+            (self.voice == other.voice, VOICE),
+            (self.nasal == other.nasal, NASAL),
+            (self.lateral == other.lateral, LATERAL),
+            (self.round == other.round, ROUND),
+            (self.airstream == other.airstream, AIRSTREAM),
+            (self.place == other.place, PLACE),
+            (self.manner == other.manner, MANNER),
+            (self.height == other.height, HEIGHT),
+            (self.backness == other.backness, BACKNESS),
+        ];
+        fields
+            .iter()
+            .filter(|(same, _)| !same)
+            .fold(0, |mask, (_, field)| mask | field)
+    }
+
+    // How far `other` is from this bundle when only the fields governed by
+    // `changed` may differ, or `None` if any other field differs.
+    pub fn distance<I>(&self, other: &Features, changed: I) -> Option<u32>
+    where
+        I: IntoIterator<Item = Feature>,
+    {
+        let allowed = changed
+            .into_iter()
+            .fold(0, |mask, feature| mask | feature.fields());
+        let differences = self.differences(other);
+        if differences & !allowed == 0 {
+            Some(differences.count_ones())
+        } else {
+            None
+        }
+    }
+
+    pub fn sonorant(&self) -> bool {
+        match self.manner {
+            None => true,
+            Some(manner) => matches!(
+                manner,
+                Manner::Nasal
+                    | Manner::Trill
+                    | Manner::Tap
+                    | Manner::Approximant
+            ),
+        }
+    }
+}
+
+// Looks up the feature bundle of one segment of the inventory. Diacritics and
+// the ejective mark modify the segment they follow, and a sequence of two
+// segments is read as an affricate or, when tied, a doubly articulated stop.
+pub fn features(symbol: &str) -> Option<Features> {
+    let mut segments = Vec::<Features>::new();
+    let mut tied = false;
+
+    for ch in symbol.chars() {
+        if ch == '\u{361}' {
+            tied = true;
+            continue;
+        }
+        match (segments.last_mut(), base_features(ch)) {
+            (_, Some(features)) => segments.push(features),
+            (Some(last), None) => *last = modify(*last, ch)?,
+            (None, None) => return None,
+        }
+    }
+
+    match segments.as_slice() {
+        [only] => Some(*only),
+        [first, second] => combine(*first, *second, tied),
+        _ => None,
+    }
+}
+
+fn combine(first: Features, second: Features, tied: bool) -> Option<Features> {
+    let manner = (first.manner?, second.manner?);
+    if tied {
+        let place = match (first.place?, second.place?) {
+            (Place::Velar, Place::Bilabial) => Place::LabialVelar,
+            (place, _) => place,
+        };
+        Some(Features { place: Some(place), ..first })
+    } else if manner == (Manner::Stop, Manner::Fricative) {
+        Some(Features { manner: Some(Manner::Affricate), ..second })
+    } else {
+        Some(Features { manner: second.manner, ..first })
+    }
+}
+
+fn modify(features: Features, diacritic: char) -> Option<Features> {
+    let mut features = features;
+    match diacritic {
+        '\u{325}' | '\u{30a}' => features.voice = false,
+        '\u{32c}' => features.voice = true,
+        '\u{303}' => features.nasal = true,
+        'ʼ' => features.airstream = Airstream::Ejective,
+        '\u{32a}' => features.place = Some(Place::Dental),
+        '\u{33c}' => features.place = Some(Place::Linguolabial),
+        '\u{308}' => features.backness = Some(Backness::Central),
+        '\u{320}' => {
+            if let Some(Place::Dental) | Some(Place::Alveolar) = features.place
+            {
+                features.place = Some(Place::Postalveolar);
+            }
+        },
+        '\u{31f}' => {
+            if let Some(Place::Labiodental) = features.place {
+                features.place = Some(Place::Bilabial);
+            }
+        },
+        '\u{31d}' | '\u{2d4}' => {
+            if let Some(Manner::Approximant) = features.manner {
+                features.manner = Some(Manner::Fricative);
+            }
+        },
+        '\u{31e}' => {
+            features.manner = match features.manner {
+                Some(Manner::Stop) => Some(Manner::Fricative),
+                Some(Manner::Fricative) => Some(Manner::Approximant),
+                manner => manner,
+            };
+            features.height = features.height.map(lower);
+        },
+        '\u{306}' => {
+            if let Some(Manner::Stop) | Some(Manner::Approximant) =
+                features.manner
+            {
+                features.manner = Some(Manner::Tap);
+            }
+        },
+        _ => return None,
+    }
+    Some(features)
+}
+
+fn lower(height: Height) -> Height {
+    match height {
+        Height::Close => Height::NearClose,
+        Height::NearClose => Height::CloseMid,
+        Height::CloseMid => Height::Mid,
+        Height::Mid => Height::OpenMid,
+        Height::OpenMid => Height::NearOpen,
+        Height::NearOpen | Height::Open => Height::Open,
+    }
+}
+
+fn base_features(ch: char) -> Option<Features> {
+    use self::{
+        Airstream::{Click, Implosive},
+        Backness::*,
+        Height::*,
+        Manner::*,
+        Place::*,
+    };
+
+    let cons = Features::consonant;
+    let vowel = Features::vowel;
+
+    Some(match ch {
+        'p' => cons(Bilabial, Stop, false),
+        'b' => cons(Bilabial, Stop, true),
+        'ʬ' => cons(Bilabial, Stop, false),
+        'm' => cons(Bilabial, Nasal, true),
+        'ʙ' => cons(Bilabial, Trill, true),
+        'ɸ' => cons(Bilabial, Fricative, false),
+        'β' => cons(Bilabial, Fricative, true),
+        'ɓ' => cons(Bilabial, Stop, true).airstream(Implosive),
+        'ʘ' => cons(Bilabial, Stop, false).airstream(Click),
+
+        'ɱ' => cons(Labiodental, Nasal, true),
+        'ⱱ' => cons(Labiodental, Tap, true),
+        'f' => cons(Labiodental, Fricative, false),
+        'v' => cons(Labiodental, Fricative, true),
+        'ʋ' => cons(Labiodental, Approximant, true),
+
+        'ʭ' => cons(Dental, Stop, false),
+        'θ' => cons(Dental, Fricative, false),
+        'ð' => cons(Dental, Fricative, true),
+        'ǀ' => cons(Dental, Stop, false).airstream(Click),
+
+        't' => cons(Alveolar, Stop, false),
+        'd' => cons(Alveolar, Stop, true),
+        'n' => cons(Alveolar, Nasal, true),
+        'r' => cons(Alveolar, Trill, true),
+        'ɾ' => cons(Alveolar, Tap, true),
+        's' => cons(Alveolar, Fricative, false),
+        'z' => cons(Alveolar, Fricative, true),
+        'ɹ' => cons(Alveolar, Approximant, true),
+        'ɬ' => cons(Alveolar, Fricative, false).lateral(),
+        'ɮ' => cons(Alveolar, Fricative, true).lateral(),
+        'l' | 'ɫ' => cons(Alveolar, Approximant, true).lateral(),
+        'ɺ' => cons(Alveolar, Tap, true).lateral(),
+        'ɗ' => cons(Alveolar, Stop, true).airstream(Implosive),
+        'ǃ' => cons(Alveolar, Stop, false).airstream(Click),
+        'ǁ' => cons(Alveolar, Stop, false).lateral().airstream(Click),
+
+        'ʃ' => cons(Postalveolar, Fricative, false),
+        'ʒ' => cons(Postalveolar, Fricative, true),
+
+        'ʈ' => cons(Retroflex, Stop, false),
+        'ɖ' => cons(Retroflex, Stop, true),
+        'ɳ' => cons(Retroflex, Nasal, true),
+        'ɽ' => cons(Retroflex, Tap, true),
+        'ʂ' => cons(Retroflex, Fricative, false),
+        'ʐ' => cons(Retroflex, Fricative, true),
+        'ɻ' => cons(Retroflex, Approximant, true),
+        'ꞎ' => cons(Retroflex, Fricative, false).lateral(),
+        'ɭ' => cons(Retroflex, Approximant, true).lateral(),
+        'ᶑ' => cons(Retroflex, Stop, true).airstream(Implosive),
+        '‼' => cons(Retroflex, Stop, false).airstream(Click),
+
+        'ȶ' => cons(AlveoloPalatal, Stop, false),
+        'ȡ' => cons(AlveoloPalatal, Stop, true),
+        'ȵ' => cons(AlveoloPalatal, Nasal, true),
+        'ɕ' => cons(AlveoloPalatal, Fricative, false),
+        'ʑ' => cons(AlveoloPalatal, Fricative, true),
+        'ȴ' => cons(AlveoloPalatal, Approximant, true).lateral(),
+
+        'c' => cons(Palatal, Stop, false),
+        'ɟ' => cons(Palatal, Stop, true),
+        'ɲ' => cons(Palatal, Nasal, true),
+        'ç' => cons(Palatal, Fricative, false),
+        'ʝ' => cons(Palatal, Fricative, true),
+        'j' => cons(Palatal, Approximant, true),
+        'ʎ' => cons(Palatal, Approximant, true).lateral(),
+        'ʄ' => cons(Palatal, Stop, true).airstream(Implosive),
+        'ǂ' => cons(Palatal, Stop, false).airstream(Click),
+
+        'ɥ' => cons(LabialPalatal, Approximant, true).round(),
+
+        'k' => cons(Velar, Stop, false),
+        // ASCII `g` is commonly typed for the IPA `ɡ`.
+        'g' | 'ɡ' => cons(Velar, Stop, true),
+        'ŋ' => cons(Velar, Nasal, true),
+        'x' | 'ɧ' => cons(Velar, Fricative, false),
+        'ɣ' => cons(Velar, Fricative, true),
+        'ɰ' => cons(Velar, Approximant, true),
+        'ʟ' => cons(Velar, Approximant, true).lateral(),
+        'ɠ' => cons(Velar, Stop, true).airstream(Implosive),
+        'ʞ' => cons(Velar, Stop, false).airstream(Click),
+
+        'ʍ' => cons(LabialVelar, Fricative, false).round(),
+        'w' => cons(LabialVelar, Approximant, true).round(),
+
+        'q' => cons(Uvular, Stop, false),
+        'ɢ' => cons(Uvular, Stop, true),
+        'ɴ' => cons(Uvular, Nasal, true),
+        'ʀ' => cons(Uvular, Trill, true),
+        'χ' => cons(Uvular, Fricative, false),
+        'ʁ' => cons(Uvular, Fricative, true),
+        'ʛ' => cons(Uvular, Stop, true).airstream(Implosive),
+
+        'ħ' => cons(Pharyngeal, Fricative, false),
+        'ʕ' => cons(Pharyngeal, Fricative, true),
+
+        'ʡ' => cons(Epiglottal, Stop, false),
+        'ʜ' => cons(Epiglottal, Fricative, false),
+        'ʢ' => cons(Epiglottal, Fricative, true),
+
+        'ʔ' => cons(Glottal, Stop, false),
+        'h' => cons(Glottal, Fricative, false),
+        'ɦ' => cons(Glottal, Fricative, true),
+
+        'i' => vowel(Close, Front, false),
+        'y' => vowel(Close, Front, true),
+        'ɨ' => vowel(Close, Central, false),
+        'ʉ' => vowel(Close, Central, true),
+        'ɯ' => vowel(Close, Back, false),
+        'u' => vowel(Close, Back, true),
+        'ɪ' => vowel(NearClose, Front, false),
+        'ʏ' => vowel(NearClose, Front, true),
+        'ʊ' => vowel(NearClose, Back, true),
+        'e' => vowel(CloseMid, Front, false),
+        'ø' => vowel(CloseMid, Front, true),
+        'ɘ' => vowel(CloseMid, Central, false),
+        'ɵ' => vowel(CloseMid, Central, true),
+        'ɤ' => vowel(CloseMid, Back, false),
+        'o' => vowel(CloseMid, Back, true),
+        'ə' => vowel(Mid, Central, false),
+        'ɛ' => vowel(OpenMid, Front, false),
+        'œ' => vowel(OpenMid, Front, true),
+        'ɜ' => vowel(OpenMid, Central, false),
+        'ɞ' => vowel(OpenMid, Central, true),
+        'ʌ' => vowel(OpenMid, Back, false),
+        'ɔ' => vowel(OpenMid, Back, true),
+        'æ' => vowel(NearOpen, Front, false),
+        'ɐ' => vowel(NearOpen, Central, false),
+        'ä' => vowel(Open, Central, false),
+        'a' => vowel(Open, Front, false),
+        'ɶ' => vowel(Open, Front, true),
+        'ɑ' => vowel(Open, Back, false),
+        'ɒ' => vowel(Open, Back, true),
+
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        features,
+        lookup,
+        Airstream,
+        Backness,
+        Category,
+        Feature,
+        Height,
+        Manner,
+        Place,
+        SYMBOLS,
+    };
+    use unicode_normalization::is_nfc;
+
+    #[test]
+    fn symbol_table() {
+        for pair in SYMBOLS.windows(2) {
+            assert!(
+                pair[0].symbol < pair[1].symbol,
+                "{} and {} are out of order",
+                pair[0].symbol,
+                pair[1].symbol
+            );
+        }
+        for entry in SYMBOLS {
+            assert!(is_nfc(entry.symbol), "{} is not in NFC", entry.symbol);
+        }
+
+        let category = |symbol| lookup(symbol).map(|entry| entry.category);
+        assert_eq!(category("p"), Some(Category::Pulmonic));
+        assert_eq!(category("ǃ"), Some(Category::NonPulmonic));
+        assert_eq!(category("ɓ"), Some(Category::NonPulmonic));
+        assert_eq!(category("tsʼ"), Some(Category::NonPulmonic));
+        assert_eq!(category("t̠ʃ"), Some(Category::Other));
+        assert_eq!(category("w"), Some(Category::Other));
+        assert_eq!(category("a"), Some(Category::Vowel));
+        // Decomposed `ä` is looked up by its NFC form only.
+        assert_eq!(category("a\u{308}"), None);
+        assert_eq!(category("ʼ"), None);
+    }
+
+    #[test]
+    fn whole_inventory() {
+        for entry in SYMBOLS {
+            let symbol = entry.symbol;
+            assert!(features(symbol).is_some(), "no features for {}", symbol);
+        }
+        assert!(features("").is_none());
+        assert!(features("ʼ").is_none());
+        assert!(features("tsk").is_none());
+    }
+
+    #[test]
+    fn plain_segments() {
+        let g = features("ɡ").unwrap();
+        assert!(g.voice && g.consonantal() && !g.sonorant());
+        assert_eq!(g.place, Some(Place::Velar));
+        assert_eq!(g.manner, Some(Manner::Stop));
+        assert_eq!(features("g"), Some(g));
+
+        let j = features("j").unwrap();
+        assert!(!j.consonantal() && j.sonorant() && !j.syllabic);
+
+        let o = features("o").unwrap();
+        assert!(o.syllabic && o.round && !o.consonantal());
+        assert_eq!(o.height, Some(Height::CloseMid));
+        assert_eq!(o.backness, Some(Backness::Back));
+    }
+
+    #[test]
+    fn diacritics_and_sequences() {
+        let n = features("n̥").unwrap();
+        assert!(!n.voice && n.nasal);
+
+        let ejective = features("tsʼ").unwrap();
+        assert_eq!(ejective.manner, Some(Manner::Affricate));
+        assert_eq!(ejective.place, Some(Place::Alveolar));
+        assert_eq!(ejective.airstream, Airstream::Ejective);
+
+        let lateral = features("cʎ̝̊").unwrap();
+        assert!(lateral.lateral && !lateral.voice);
+        assert_eq!(lateral.manner, Some(Manner::Affricate));
+
+        let kp = features("k͡p").unwrap();
+        assert_eq!(kp.place, Some(Place::LabialVelar));
+        assert_eq!(kp.manner, Some(Manner::Stop));
+
+        let postalveolar = features("t̠ʃ").unwrap();
+        assert_eq!(postalveolar.place, Some(Place::Postalveolar));
+
+        assert_eq!(features("e̞").unwrap().height, Some(Height::Mid));
+        assert_eq!(features("ä").unwrap().backness, Some(Backness::Central));
+    }
+
+    #[test]
+    fn feature_names() {
+        let stop = Feature::from_name("stop").unwrap();
+        assert_eq!(stop, Feature::Manner(Manner::Stop));
+        assert_eq!(stop.name(), "stop");
+        assert!(Feature::from_name("stops").is_none());
+
+        let p = features("p").unwrap();
+        assert!(
+            p.has(stop) && p.has(Feature::Labial) && !p.has(Feature::Voice)
+        );
+        let a = features("a").unwrap();
+        assert!(a.has(Feature::Low) && a.has(Feature::Continuant));
+        assert!(!a.has(Feature::Labial));
+    }
+
+    #[test]
+    fn distance() {
+        let p = features("p").unwrap();
+        let b = features("b").unwrap();
+        let f = features("f").unwrap();
+        let voice = Some(Feature::Voice);
+
+        assert_eq!(p.distance(&b, voice), Some(1));
+        assert_eq!(p.distance(&p, voice), Some(0));
+        assert_eq!(p.distance(&f, voice), None);
+        assert_eq!(
+            p.distance(&f, Some(Feature::Manner(Manner::Fricative))),
+            None
+        );
+        let changed = vec![Feature::Manner(Manner::Fricative), Feature::Labial];
+        assert_eq!(p.distance(&f, changed), Some(2));
+    }
+}
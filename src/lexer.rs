@@ -29,9 +29,10 @@ impl Lexer {
     }
 
     pub fn is_eof(&self) -> bool {
-        self.curr().ok().map_or(false, |tok| tok.kind == TokenKind::Eof)
+        self.curr().ok().is_some_and(|tok| tok.kind == TokenKind::Eof)
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn curr(&self) -> Result<Token, ()> {
         self.toks[self.pos].clone()
     }
@@ -44,15 +45,14 @@ impl Lexer {
         self.rollback(1) == 1
     }
 
+    // Erroneous tokens are advanced past like any other, so that the parser
+    // can recover after an unsupported character. Only EOF stops the lexer.
     pub fn advance(&mut self, count: usize, errs: &mut Diagnostic) -> usize {
         let mut advanced = count.min(self.toks.len() - self.pos - 1);
         self.pos += advanced;
 
-        while self
-            .curr()
-            .ok()
-            .filter(|tok| tok.kind != TokenKind::Eof && advanced < count)
-            .is_some()
+        while advanced < count
+            && self.curr().map_or(true, |tok| tok.kind != TokenKind::Eof)
         {
             let tok = self.read(errs);
             self.toks.push(tok);
@@ -68,6 +68,7 @@ impl Lexer {
         rolled
     }
 
+    #[allow(clippy::result_unit_err, clippy::unit_arg)]
     pub fn check<P>(&self, pat: P, errs: &mut Diagnostic) -> Result<Token, ()>
     where
         P: TokenPattern,
@@ -82,6 +83,7 @@ impl Lexer {
         }
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn expect<P>(
         &mut self,
         pat: P,
@@ -114,6 +116,40 @@ impl Lexer {
             self.read_open_paren(errs)
         } else if self.is_close_paren() {
             self.read_close_paren(errs)
+        } else if self.is_greater() {
+            self.read_greater(errs)
+        } else if self.is_slash() {
+            self.read_slash(errs)
+        } else if self.is_underscore() {
+            self.read_underscore(errs)
+        } else if self.is_boundary() {
+            self.read_boundary(errs)
+        } else if self.is_question() {
+            self.read_question(errs)
+        } else if self.is_star() {
+            self.read_star(errs)
+        } else if self.is_plus() {
+            self.read_plus(errs)
+        } else if self.is_open_brace() {
+            self.read_open_brace(errs)
+        } else if self.is_close_brace() {
+            self.read_close_brace(errs)
+        } else if self.is_minus() {
+            self.read_minus(errs)
+        } else if self.is_ampersand() {
+            self.read_ampersand(errs)
+        } else if self.is_bang() {
+            self.read_bang(errs)
+        } else if self.is_null() {
+            self.read_null(errs)
+        } else if self.is_index() {
+            self.read_index(errs)
+        } else if self.is_at() {
+            self.read_at(errs)
+        } else if self.is_open_bracket() {
+            self.read_open_bracket(errs)
+        } else if self.is_close_bracket() {
+            self.read_close_bracket(errs)
         } else {
             self.read_eof(errs)
         }
@@ -157,45 +193,115 @@ impl Lexer {
     }
 
     fn is_whitespace(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch.contains(char::is_whitespace))
+        self.reader.curr().is_some_and(|ch| ch.contains(char::is_whitespace))
     }
 
     fn is_quoted_start(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == "'")
+        self.reader.curr().is_some_and(|ch| ch == "'")
     }
 
     fn is_unquoted(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| {
-            ch == "_"
-                || ch.len() == 1 && ch >= "a" && ch <= "z"
-                || ch.len() == 1 && ch >= "A" && ch <= "Z"
-                || ch.len() == 1 && ch >= "0" && ch <= "9"
-                || ipa::SYMBOLS.binary_search(&ch).is_ok()
+        self.reader.curr().is_some_and(|ch| {
+            ch.len() == 1 && ("a" ..= "z").contains(&ch)
+                || ch.len() == 1 && ("A" ..= "Z").contains(&ch)
+                || ch.len() == 1 && ("0" ..= "9").contains(&ch)
+                || ipa::is_symbol(ch)
         })
     }
 
     fn is_class_ident_start(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == "\\")
+        self.reader.curr().is_some_and(|ch| ch == "\\")
     }
 
     fn is_equal_symbol(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == "=")
+        self.reader.curr().is_some_and(|ch| ch == "=")
     }
 
     fn is_comma(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == ",")
+        self.reader.curr().is_some_and(|ch| ch == ",")
     }
 
     fn is_pipe(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == "|")
+        self.reader.curr().is_some_and(|ch| ch == "|")
     }
 
     fn is_open_paren(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == "(")
+        self.reader.curr().is_some_and(|ch| ch == "(")
     }
 
     fn is_close_paren(&self) -> bool {
-        self.reader.curr().map_or(false, |ch| ch == ")")
+        self.reader.curr().is_some_and(|ch| ch == ")")
+    }
+
+    fn is_greater(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == ">")
+    }
+
+    fn is_slash(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "/")
+    }
+
+    fn is_underscore(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "_")
+    }
+
+    fn is_boundary(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "#")
+    }
+
+    fn is_question(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "?")
+    }
+
+    fn is_star(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "*")
+    }
+
+    fn is_plus(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "+")
+    }
+
+    fn is_open_brace(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "{")
+    }
+
+    fn is_close_brace(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "}")
+    }
+
+    fn is_minus(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "-")
+    }
+
+    fn is_ampersand(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "&")
+    }
+
+    fn is_bang(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "!")
+    }
+
+    fn is_null(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "∅")
+    }
+
+    fn is_index(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| {
+            let mut chars = ch.chars();
+            matches!((chars.next(), chars.next()), (Some('₀' ..= '₉'), None))
+        })
+    }
+
+    fn is_at(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "@")
+    }
+
+    fn is_open_bracket(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "[")
+    }
+
+    fn is_close_bracket(&self) -> bool {
+        self.reader.curr().is_some_and(|ch| ch == "]")
     }
 
     fn read_unquoted(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
@@ -208,6 +314,14 @@ impl Lexer {
         let kind = match &*span.content() {
             "alphabet" => TokenKind::Alphabet,
             "class" => TokenKind::Class,
+            "feature" => TokenKind::Feature,
+            "rule" => TokenKind::Rule,
+            "sequential" => TokenKind::Sequential,
+            "simultaneous" => TokenKind::Simultaneous,
+            "iterative" => TokenKind::Iterative,
+            "ltr" => TokenKind::Ltr,
+            "rtl" => TokenKind::Rtl,
+            "feeding" => TokenKind::Feeding,
             _ => TokenKind::String(span.content().to_string()),
         };
 
@@ -304,6 +418,133 @@ impl Lexer {
         Ok(Token { kind: TokenKind::CloseParen, span: self.reader.span() })
     }
 
+    fn read_greater(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Greater, span: self.reader.span() })
+    }
+
+    fn read_slash(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Slash, span: self.reader.span() })
+    }
+
+    fn read_underscore(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Underscore, span: self.reader.span() })
+    }
+
+    fn read_boundary(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Boundary, span: self.reader.span() })
+    }
+
+    fn read_question(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Question, span: self.reader.span() })
+    }
+
+    fn read_star(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Star, span: self.reader.span() })
+    }
+
+    fn read_plus(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Plus, span: self.reader.span() })
+    }
+
+    fn read_open_brace(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::OpenBrace, span: self.reader.span() })
+    }
+
+    fn read_close_brace(
+        &mut self,
+        _errs: &mut Diagnostic,
+    ) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::CloseBrace, span: self.reader.span() })
+    }
+
+    fn read_minus(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Minus, span: self.reader.span() })
+    }
+
+    fn read_ampersand(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Ampersand, span: self.reader.span() })
+    }
+
+    fn read_bang(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Bang, span: self.reader.span() })
+    }
+
+    fn read_null(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::Null, span: self.reader.span() })
+    }
+
+    #[allow(clippy::unit_arg)]
+    fn read_index(&mut self, errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        let mut digits = String::new();
+        while self.is_index() {
+            if let Some(ch) =
+                self.reader.curr().and_then(|ch| ch.chars().next())
+            {
+                let digit = ch as u32 - '₀' as u32;
+                digits.extend(char::from_digit(digit, 10));
+                self.reader.next();
+            }
+        }
+
+        let span = self.reader.span();
+        match digits.parse() {
+            Ok(index) => Ok(Token { kind: TokenKind::Index(index), span }),
+            Err(_) => Err(errs.raise(ErrorKind::BadIndex(span))),
+        }
+    }
+
+    fn read_at(&mut self, _errs: &mut Diagnostic) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::At, span: self.reader.span() })
+    }
+
+    fn read_open_bracket(
+        &mut self,
+        _errs: &mut Diagnostic,
+    ) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::OpenBracket, span: self.reader.span() })
+    }
+
+    fn read_close_bracket(
+        &mut self,
+        _errs: &mut Diagnostic,
+    ) -> Result<Token, ()> {
+        self.reader.mark();
+        self.reader.next();
+        Ok(Token { kind: TokenKind::CloseBracket, span: self.reader.span() })
+    }
+
+    #[allow(clippy::unit_arg)]
     fn read_eof(&mut self, errs: &mut Diagnostic) -> Result<Token, ()> {
         self.reader.mark();
         if self.reader.next() {
@@ -317,7 +558,11 @@ impl Lexer {
 #[cfg(test)]
 mod test {
     use super::Lexer;
-    use crate::{error::Diagnostic, source::Src, token::TokenKind};
+    use crate::{
+        error::{Diagnostic, ErrorKind},
+        source::Src,
+        token::TokenKind,
+    };
 
     #[test]
     fn parens_and_unquoted() {
@@ -351,6 +596,30 @@ mod test {
         assert_eq!(errs.as_slice().len(), 0);
     }
 
+    #[test]
+    fn advance_past_errors() {
+        let src = Src::new("foo.psh", "a % b");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::String("a".into()));
+        assert!(lexer.next(&mut errs));
+        assert!(lexer.curr().is_err());
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::String("b".into()));
+        assert!(lexer.next(&mut errs));
+        assert!(lexer.is_eof());
+        assert!(!lexer.next(&mut errs));
+
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::BadChar(span) => assert_eq!(span.as_str(), "%"),
+            kind => panic!("expected bad character, found {:?}", kind),
+        }
+    }
+
     #[test]
     fn keywords_and_commas() {
         let src = Src::new("foo.psh", "class, alphabet,");
@@ -400,6 +669,208 @@ mod test {
         assert_eq!(errs.as_slice().len(), 0);
     }
 
+    #[test]
+    fn rule_symbols() {
+        let src = Src::new("foo.psh", r"rule p > f / \V_#");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Rule);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("p".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Greater);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("f".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Slash);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::ClassIdent("V".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Underscore);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Boundary);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Eof);
+
+        assert_eq!(errs.as_slice().len(), 0);
+    }
+
+    #[test]
+    fn repetition_operators() {
+        let src = Src::new("foo.psh", r"\C?*+{1,2}");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::ClassIdent("C".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Question);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Star);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Plus);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::OpenBrace);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("1".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Comma);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("2".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::CloseBrace);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Eof);
+
+        assert_eq!(errs.as_slice().len(), 0);
+    }
+
+    #[test]
+    fn feature_matrix() {
+        let src = Src::new("foo.psh", "[+stop -voice]");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        let expected = [
+            TokenKind::OpenBracket,
+            TokenKind::Plus,
+            TokenKind::String("stop".to_owned()),
+            TokenKind::Minus,
+            TokenKind::String("voice".to_owned()),
+            TokenKind::CloseBracket,
+        ];
+        for kind in &expected {
+            assert_eq!(lexer.curr().unwrap().kind, *kind);
+            assert!(lexer.next(&mut errs));
+        }
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Eof);
+
+        assert_eq!(errs.as_slice().len(), 0);
+    }
+
+    #[test]
+    fn class_operators() {
+        let src = Src::new("foo.psh", r"!\V - p & \C");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Bang);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::ClassIdent("V".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Minus);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("p".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Ampersand);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::ClassIdent("C".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Eof);
+
+        assert_eq!(errs.as_slice().len(), 0);
+    }
+
+    #[test]
+    fn null_symbol() {
+        let src = Src::new("foo.psh", "h > ∅");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("h".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Greater);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Null);
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Eof);
+
+        assert_eq!(errs.as_slice().len(), 0);
+    }
+
+    #[test]
+    fn indices() {
+        let src = Src::new("foo.psh", r"\C₁ a₁₂");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::ClassIdent("C".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Index(1));
+        assert!(lexer.next(&mut errs));
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::String("a".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Index(12));
+        assert!(lexer.next(&mut errs));
+        assert_eq!(lexer.curr().unwrap().kind, TokenKind::Eof);
+
+        assert_eq!(errs.as_slice().len(), 0);
+    }
+
+    #[test]
+    fn index_with_mark() {
+        let src = Src::new("foo.psh", "\\C₁\u{301}");
+        let mut errs = Diagnostic::new();
+
+        let mut lexer = Lexer::new(src.reader(), &mut errs);
+
+        assert_eq!(
+            lexer.curr().unwrap().kind,
+            TokenKind::ClassIdent("C".to_owned())
+        );
+        assert!(lexer.next(&mut errs));
+        assert!(lexer.curr().is_err());
+
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::BadChar(span) => assert_eq!(span.as_str(), "₁\u{301}"),
+            kind => panic!("expected bad character, found {:?}", kind),
+        }
+    }
+
     #[test]
     fn error_unclosed() {
         let src = Src::new("foo.psh", r"'ah");
@@ -4,5 +4,12 @@ pub mod source;
 pub mod error;
 pub mod token;
 pub mod lexer;
+pub mod ast;
+pub mod parser;
+pub mod feature;
 pub mod pat;
 pub mod symbol;
+pub mod segment;
+pub mod rule;
+pub mod resolve;
+pub mod engine;
@@ -0,0 +1,121 @@
+use phoneshift::{
+    engine::Engine,
+    error::Diagnostic,
+    resolve::{self, Resolved},
+    segment::segment,
+    source::Src,
+};
+use std::{
+    env,
+    fs,
+    io::{self, BufRead, BufReader, Read},
+    process,
+};
+
+const USAGE: &str = "\
+Usage:
+    phoneshift check <RULES>
+    phoneshift apply <RULES> [WORDS]
+
+Commands:
+    check    Compiles the rule file and reports any diagnostics.
+    apply    Compiles the rule file and applies it to every line of the word
+             list, printing `input → output`. Reads words from standard
+             input if the word list is `-` or omitted.";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let code = match &*args {
+        ["check", rules] => check(rules),
+        ["apply", rules] => apply(rules, None),
+        ["apply", rules, words] => apply(rules, Some(words)),
+        ["help"] | ["-h"] | ["--help"] => {
+            println!("{}", USAGE);
+            0
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            2
+        },
+    };
+
+    process::exit(code);
+}
+
+fn check(rules: &str) -> i32 {
+    let mut errs = Diagnostic::new();
+    let result = load_rules(rules, &mut errs);
+    println!("{}", errs);
+    match result {
+        Some(_) => 0,
+        None => 1,
+    }
+}
+
+fn apply(rules: &str, words: Option<&str>) -> i32 {
+    let mut errs = Diagnostic::new();
+    let result = load_rules(rules, &mut errs);
+    if !errs.is_empty() {
+        eprintln!("{}", errs);
+    }
+    let resolved = match result {
+        Some(resolved) => resolved,
+        None => return 1,
+    };
+
+    let input: Box<dyn Read> = match words {
+        None | Some("-") => Box::new(io::stdin()),
+        Some(path) => match fs::File::open(path) {
+            Ok(file) => Box::new(file),
+            Err(err) => {
+                eprintln!("cannot open {}: {}", path, err);
+                return 1;
+            },
+        },
+    };
+
+    let engine = Engine::with_blocks(resolved.blocks);
+    let mut code = 0;
+
+    for (index, line) in BufReader::new(input).lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("cannot read {}: {}", words.unwrap_or("-"), err);
+                return 1;
+            },
+        };
+        let word = line.trim();
+        if word.is_empty() {
+            continue;
+        }
+
+        match segment(word, &resolved.terms) {
+            Ok(word) => {
+                let mut errs = Diagnostic::new();
+                println!("{} → {}", word, engine.apply(&word, &mut errs));
+                for err in errs.as_slice() {
+                    eprintln!("line {}: {}", index + 1, err);
+                }
+            },
+            Err(err) => {
+                eprintln!("line {}: {}", index + 1, err);
+                code = 1;
+            },
+        }
+    }
+
+    code
+}
+
+fn load_rules(path: &str, errs: &mut Diagnostic) -> Option<Resolved> {
+    match fs::read_to_string(path) {
+        Ok(content) => resolve::compile(&Src::new(path, content), errs).ok(),
+        Err(err) => {
+            eprintln!("cannot read {}: {}", path, err);
+            None
+        },
+    }
+}
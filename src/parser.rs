@@ -0,0 +1,1310 @@
+use crate::{
+    ast::{
+        AlphabetDecl,
+        BlockDecl,
+        BlockMode,
+        ClassDecl,
+        ClassExpr,
+        ClassExprKind,
+        Decl,
+        Env,
+        FeatureDecl,
+        FeatureSpec,
+        FeatureValue,
+        Name,
+        PatternExpr,
+        PatternExprKind,
+        Program,
+        ReplacementExpr,
+        ReplacementExprKind,
+        RuleDecl,
+        RuleModifier,
+        RuleModifierKind,
+    },
+    error::{Diagnostic, ErrorKind},
+    lexer::Lexer,
+    pat::CaptureName,
+    source::{Reader, Span},
+    token::{
+        AnyClassIdent,
+        AnyIndex,
+        AnyNull,
+        AnyNumber,
+        AnyString,
+        Token,
+        TokenKind,
+        TokenPattern,
+    },
+};
+
+const DECL_START: &[(TokenKind, &str)] = &[
+    (TokenKind::Alphabet, "alphabet"),
+    (TokenKind::Class, "class"),
+    (TokenKind::Feature, "feature"),
+    (TokenKind::Rule, "rule"),
+    (TokenKind::Sequential, "sequential"),
+    (TokenKind::Simultaneous, "simultaneous"),
+    (TokenKind::Iterative, "iterative"),
+];
+
+const POSTFIX_OPS: &[TokenKind] = &[
+    TokenKind::Question,
+    TokenKind::Star,
+    TokenKind::Plus,
+    TokenKind::OpenBrace,
+];
+
+const SET_OPS: &[TokenKind] = &[TokenKind::Minus, TokenKind::Ampersand];
+
+const CAPTURE_OPS: &[&dyn TokenPattern] = &[&AnyIndex, &TokenKind::At];
+
+const FEATURE_SIGNS: &[TokenKind] = &[TokenKind::Plus, TokenKind::Minus];
+
+const FEATURE_VALUE_START: &[&dyn TokenPattern] =
+    &[&TokenKind::Plus, &TokenKind::Minus, &AnyString];
+
+const CLASS_START: &[&dyn TokenPattern] = &[
+    &AnyString,
+    &AnyClassIdent,
+    &TokenKind::Bang,
+    &TokenKind::OpenParen,
+    &TokenKind::OpenBracket,
+];
+
+const PATTERN_START: &[&dyn TokenPattern] = &[
+    &AnyString,
+    &AnyClassIdent,
+    &TokenKind::Boundary,
+    &TokenKind::Bang,
+    &TokenKind::OpenParen,
+    &TokenKind::OpenBracket,
+    &TokenKind::Null,
+];
+
+const REPLACEMENT_START: &[&dyn TokenPattern] = &[
+    &TokenKind::Null,
+    &AnyString,
+    &AnyClassIdent,
+    &TokenKind::Bang,
+    &TokenKind::OpenParen,
+    &TokenKind::OpenBracket,
+];
+
+#[derive(Debug, Clone)]
+pub struct Parser {
+    lexer: Lexer,
+}
+
+impl Parser {
+    pub fn new(reader: Reader, errs: &mut Diagnostic) -> Self {
+        Self { lexer: Lexer::new(reader, errs) }
+    }
+
+    pub fn lexer(&self) -> &Lexer {
+        &self.lexer
+    }
+
+    #[allow(clippy::result_unit_err)]
+    pub fn parse_program(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<Program, ()> {
+        let mut decls = Vec::new();
+        let mut failed = false;
+
+        while !self.lexer.is_eof() {
+            match self.parse_decl(errs) {
+                Ok(decl) => decls.push(decl),
+                Err(()) => {
+                    failed = true;
+                    self.recover(errs);
+                },
+            }
+        }
+
+        if failed {
+            Err(())
+        } else {
+            Ok(Program { decls })
+        }
+    }
+
+    fn recover(&mut self, errs: &mut Diagnostic) {
+        while self.lexer.curr().map_or(true, |tok| {
+            tok.kind != TokenKind::Eof && !DECL_START.test(&tok)
+        }) {
+            self.lexer.next(errs);
+        }
+    }
+
+    // Skips to the brace closing the current block, so that it is not
+    // reported again at the top level. Braces of repetitions are nested.
+    fn recover_block(&mut self, errs: &mut Diagnostic) {
+        let mut depth = 0usize;
+        loop {
+            let tok = match self.lexer.curr() {
+                Ok(tok) => tok,
+                Err(()) => {
+                    self.lexer.next(errs);
+                    continue;
+                },
+            };
+            match tok.kind {
+                TokenKind::Eof => break,
+                TokenKind::Rule => (),
+                _ if DECL_START.test(&tok) => break,
+                TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseBrace if depth == 0 => {
+                    self.lexer.next(errs);
+                    break;
+                },
+                TokenKind::CloseBrace => depth -= 1,
+                _ => (),
+            }
+            self.lexer.next(errs);
+        }
+    }
+
+    fn is_curr<P>(&self, pat: P) -> bool
+    where
+        P: TokenPattern,
+    {
+        self.lexer.curr().ok().is_some_and(|tok| pat.test(&tok))
+    }
+
+    fn parse_decl(&mut self, errs: &mut Diagnostic) -> Result<Decl, ()> {
+        let tok = self.lexer.check(DECL_START, errs)?;
+
+        match tok.kind {
+            TokenKind::Alphabet => {
+                self.parse_alphabet(errs).map(Decl::Alphabet)
+            },
+            TokenKind::Class => self.parse_class(errs).map(Decl::Class),
+            TokenKind::Feature => self.parse_feature(errs).map(Decl::Feature),
+            TokenKind::Rule => self.parse_rule(errs).map(Decl::Rule),
+            _ => self.parse_block(errs).map(Decl::Block),
+        }
+    }
+
+    fn parse_alphabet(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<AlphabetDecl, ()> {
+        let keyword = self.lexer.expect(TokenKind::Alphabet, errs)?;
+        let mut symbols = vec![self.parse_string(errs)?];
+
+        while self.is_curr(TokenKind::Comma) {
+            self.lexer.next(errs);
+            symbols.push(self.parse_string(errs)?);
+        }
+
+        let span = keyword.span.join(&symbols[symbols.len() - 1].span);
+        Ok(AlphabetDecl { symbols, span })
+    }
+
+    fn parse_class(&mut self, errs: &mut Diagnostic) -> Result<ClassDecl, ()> {
+        let keyword = self.lexer.expect(TokenKind::Class, errs)?;
+        let name = self.parse_class_ident(errs)?;
+        self.lexer.expect(TokenKind::Eq, errs)?;
+        let expr = self.parse_class_expr(errs)?;
+
+        let span = keyword.span.join(&expr.span);
+        Ok(ClassDecl { name, expr, span })
+    }
+
+    fn parse_feature(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<FeatureDecl, ()> {
+        let keyword = self.lexer.expect(TokenKind::Feature, errs)?;
+        let name = self.parse_string(errs)?;
+        self.lexer.expect(TokenKind::Eq, errs)?;
+
+        let mut values = vec![self.parse_feature_value(errs)?];
+        while self.is_curr(FEATURE_VALUE_START) {
+            values.push(self.parse_feature_value(errs)?);
+        }
+
+        let span = keyword.span.join(&values[values.len() - 1].span);
+        Ok(FeatureDecl { name, values, span })
+    }
+
+    fn parse_feature_value(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<FeatureValue, ()> {
+        let tok = self.lexer.check(FEATURE_VALUE_START, errs)?;
+        self.lexer.next(errs);
+        let value = match tok.kind {
+            TokenKind::Plus => Name { content: "+".to_owned(), span: tok.span },
+            TokenKind::Minus => {
+                Name { content: "-".to_owned(), span: tok.span }
+            },
+            _ => into_name(tok),
+        };
+
+        let members = self.parse_class_atom(errs)?;
+        let span = value.span.join(&members.span);
+        Ok(FeatureValue { value, members, span })
+    }
+
+    fn parse_class_expr(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<ClassExpr, ()> {
+        let mut expr = self.parse_class_term(errs)?;
+
+        while self.is_curr(TokenKind::Pipe) {
+            self.lexer.next(errs);
+            let right = self.parse_class_term(errs)?;
+            let span = expr.span.join(&right.span);
+            let kind = ClassExprKind::Or(Box::new(expr), Box::new(right));
+            expr = ClassExpr { kind, span };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_class_term(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<ClassExpr, ()> {
+        let mut expr = self.parse_class_atom(errs)?;
+
+        while self.is_curr(SET_OPS) {
+            let tok = self.lexer.curr()?;
+            self.lexer.next(errs);
+            let right = self.parse_class_atom(errs)?;
+            let span = expr.span.join(&right.span);
+            let (left, right) = (Box::new(expr), Box::new(right));
+            let kind = match tok.kind {
+                TokenKind::Minus => ClassExprKind::Diff(left, right),
+                _ => ClassExprKind::Intersect(left, right),
+            };
+            expr = ClassExpr { kind, span };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_class_atom(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<ClassExpr, ()> {
+        let tok = self.lexer.check(CLASS_START, errs)?;
+
+        match tok.kind {
+            TokenKind::Bang => {
+                self.lexer.next(errs);
+                let inner = self.parse_class_atom(errs)?;
+                let span = tok.span.join(&inner.span);
+                let kind = ClassExprKind::Not(Box::new(inner));
+                Ok(ClassExpr { kind, span })
+            },
+
+            TokenKind::OpenParen => {
+                self.lexer.next(errs);
+                let inner = self.parse_class_expr(errs)?;
+                let close = self.lexer.expect(TokenKind::CloseParen, errs)?;
+                let span = tok.span.join(&close.span);
+                let kind = ClassExprKind::Group(Box::new(inner));
+                Ok(ClassExpr { kind, span })
+            },
+
+            TokenKind::OpenBracket => {
+                let (specs, span) = self.parse_matrix(errs)?;
+                let kind = ClassExprKind::Matrix(specs);
+                Ok(ClassExpr { kind, span })
+            },
+
+            TokenKind::ClassIdent(name) => {
+                self.lexer.next(errs);
+                let kind = ClassExprKind::Class(name);
+                Ok(ClassExpr { kind, span: tok.span })
+            },
+
+            TokenKind::String(content) => {
+                self.lexer.next(errs);
+                let kind = ClassExprKind::Symbol(content);
+                Ok(ClassExpr { kind, span: tok.span })
+            },
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_rule(&mut self, errs: &mut Diagnostic) -> Result<RuleDecl, ()> {
+        let keyword = self.lexer.expect(TokenKind::Rule, errs)?;
+
+        let mut modifiers = Vec::new();
+        loop {
+            let kind = if self.is_curr(TokenKind::Ltr) {
+                RuleModifierKind::Ltr
+            } else if self.is_curr(TokenKind::Rtl) {
+                RuleModifierKind::Rtl
+            } else if self.is_curr(TokenKind::Feeding) {
+                RuleModifierKind::Feeding
+            } else {
+                break;
+            };
+            let span = self.lexer.curr()?.span;
+            modifiers.push(RuleModifier { kind, span });
+            self.lexer.next(errs);
+        }
+
+        let target = match self
+            .parse_whole_null(|tok| tok.kind == TokenKind::Greater, errs)
+        {
+            Some(span) => PatternExpr { kind: PatternExprKind::Null, span },
+            None => self.parse_pattern(errs)?,
+        };
+        self.lexer.expect(TokenKind::Greater, errs)?;
+
+        let replacement = match self
+            .parse_whole_null(|tok| !REPLACEMENT_START.test(tok), errs)
+        {
+            Some(span) => {
+                vec![ReplacementExpr { kind: ReplacementExprKind::Null, span }]
+            },
+            None => {
+                let mut replacement = vec![self.parse_replacement(errs)?];
+                while self.is_curr(REPLACEMENT_START) {
+                    replacement.push(self.parse_replacement(errs)?);
+                }
+                replacement
+            },
+        };
+
+        let env = if self.is_curr(TokenKind::Slash) {
+            Some(self.parse_env(errs)?)
+        } else {
+            None
+        };
+
+        let end = match &env {
+            Some(env) => &env.span,
+            None => &replacement[replacement.len() - 1].span,
+        };
+        let span = keyword.span.join(end);
+        Ok(RuleDecl { modifiers, target, replacement, env, span })
+    }
+
+    fn parse_block(&mut self, errs: &mut Diagnostic) -> Result<BlockDecl, ()> {
+        let keyword = self.lexer.curr()?;
+        self.lexer.next(errs);
+        let mode = match keyword.kind {
+            TokenKind::Simultaneous => BlockMode::Simultaneous,
+            TokenKind::Iterative => BlockMode::Iterative,
+            _ => BlockMode::Sequential,
+        };
+
+        self.lexer.expect(TokenKind::OpenBrace, errs)?;
+        let mut rules = Vec::new();
+        while self.is_curr(TokenKind::Rule) {
+            match self.parse_rule(errs) {
+                Ok(rule) => rules.push(rule),
+                Err(()) => {
+                    self.recover_block(errs);
+                    return Err(());
+                },
+            }
+        }
+        let close = self.lexer.expect(TokenKind::CloseBrace, errs)?;
+
+        let span = keyword.span.join(&close.span);
+        Ok(BlockDecl { mode, rules, span })
+    }
+
+    // Reads a null symbol making up a whole side of a rule, i.e. one followed
+    // by a token satisfying `ends`.
+    fn parse_whole_null<F>(
+        &mut self,
+        ends: F,
+        errs: &mut Diagnostic,
+    ) -> Option<Span>
+    where
+        F: FnOnce(&Token) -> bool,
+    {
+        let tok = self.lexer.curr().ok().filter(|tok| AnyNull.test(tok))?;
+        self.lexer.next(errs);
+        if self.lexer.curr().is_ok_and(|next| ends(&next)) {
+            Some(tok.span)
+        } else {
+            self.lexer.prev();
+            None
+        }
+    }
+
+    fn parse_replacement(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<ReplacementExpr, ()> {
+        let tok = self.lexer.check(REPLACEMENT_START, errs)?;
+
+        if tok.kind == TokenKind::Null {
+            self.lexer.next(errs);
+            let kind = ReplacementExprKind::Null;
+            return Ok(ReplacementExpr { kind, span: tok.span });
+        }
+
+        let expr = self.parse_pattern_term(errs)?;
+        let span = expr.span.clone();
+        let kind = match expr.kind {
+            PatternExprKind::Symbol(content) => {
+                ReplacementExprKind::Symbol(content)
+            },
+            PatternExprKind::Capture(inner, name) => {
+                ReplacementExprKind::Ref(*inner, name)
+            },
+            PatternExprKind::Matrix(specs) => {
+                ReplacementExprKind::Matrix(specs)
+            },
+            _ => ReplacementExprKind::Class(expr),
+        };
+        Ok(ReplacementExpr { kind, span })
+    }
+
+    fn parse_env(&mut self, errs: &mut Diagnostic) -> Result<Env, ()> {
+        let slash = self.lexer.expect(TokenKind::Slash, errs)?;
+
+        let left = if self.is_curr(TokenKind::Underscore) {
+            None
+        } else {
+            Some(self.parse_pattern(errs)?)
+        };
+
+        let focus = self.lexer.expect(TokenKind::Underscore, errs)?;
+
+        let right = if self.is_curr(PATTERN_START) {
+            Some(self.parse_pattern(errs)?)
+        } else {
+            None
+        };
+
+        let end = right.as_ref().map_or(&focus.span, |right| &right.span);
+        let span = slash.span.join(end);
+        Ok(Env { left, right, span })
+    }
+
+    fn parse_pattern(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<PatternExpr, ()> {
+        let mut pat = self.parse_pattern_seq(errs)?;
+
+        while self.is_curr(TokenKind::Pipe) {
+            self.lexer.next(errs);
+            let right = self.parse_pattern_seq(errs)?;
+            let span = pat.span.join(&right.span);
+            let kind = PatternExprKind::Or(Box::new(pat), Box::new(right));
+            pat = PatternExpr { kind, span };
+        }
+
+        Ok(pat)
+    }
+
+    fn parse_pattern_seq(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<PatternExpr, ()> {
+        let mut pat = self.parse_pattern_term(errs)?;
+
+        while self.is_curr(PATTERN_START) {
+            let right = self.parse_pattern_term(errs)?;
+            let span = pat.span.join(&right.span);
+            let kind = PatternExprKind::And(Box::new(pat), Box::new(right));
+            pat = PatternExpr { kind, span };
+        }
+
+        Ok(pat)
+    }
+
+    fn parse_pattern_term(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<PatternExpr, ()> {
+        let mut pat = self.parse_pattern_postfix(errs)?;
+
+        while self.is_curr(SET_OPS) {
+            let tok = self.lexer.curr()?;
+            self.lexer.next(errs);
+            let right = self.parse_pattern_postfix(errs)?;
+            let span = pat.span.join(&right.span);
+            let (left, right) = (Box::new(pat), Box::new(right));
+            let kind = match tok.kind {
+                TokenKind::Minus => PatternExprKind::Diff(left, right),
+                _ => PatternExprKind::Intersect(left, right),
+            };
+            pat = PatternExpr { kind, span };
+        }
+
+        Ok(pat)
+    }
+
+    fn parse_pattern_postfix(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<PatternExpr, ()> {
+        let mut pat = self.parse_pattern_atom(errs)?;
+
+        if self.is_curr(CAPTURE_OPS) {
+            let (name, end) = self.parse_capture_name(errs)?;
+            let span = pat.span.join(&end);
+            let kind = PatternExprKind::Capture(Box::new(pat), name);
+            pat = PatternExpr { kind, span };
+        }
+
+        while self.is_curr(POSTFIX_OPS) {
+            let tok = self.lexer.curr()?;
+            self.lexer.next(errs);
+            let start = pat.span.clone();
+            let inner = Box::new(pat);
+
+            let (kind, end) = match tok.kind {
+                TokenKind::Question => (PatternExprKind::Optional(inner), tok),
+                TokenKind::Star => (PatternExprKind::Star(inner), tok),
+                TokenKind::Plus => (PatternExprKind::Plus(inner), tok),
+                _ => {
+                    let (min, max, close) = self.parse_repeat_bounds(errs)?;
+                    let span = tok.span.join(&close.span);
+                    if max.is_some_and(|max| max < min) {
+                        errs.raise(ErrorKind::BadRepetition(span));
+                        return Err(());
+                    }
+                    (PatternExprKind::Repeat(inner, min, max), close)
+                },
+            };
+
+            let span = start.join(&end.span);
+            pat = PatternExpr { kind, span };
+        }
+
+        Ok(pat)
+    }
+
+    fn parse_capture_name(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<(CaptureName, Span), ()> {
+        let tok = self.lexer.check(CAPTURE_OPS, errs)?;
+        self.lexer.next(errs);
+
+        match tok.kind {
+            TokenKind::Index(index) => {
+                Ok((CaptureName::Index(index), tok.span))
+            },
+            _ => {
+                let name = self.parse_string(errs)?;
+                let span = tok.span.join(&name.span);
+                Ok((CaptureName::Name(name.content), span))
+            },
+        }
+    }
+
+    fn parse_repeat_bounds(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<(usize, Option<usize>, Token), ()> {
+        let min = self.parse_number(errs)?;
+
+        let max = if self.is_curr(TokenKind::Comma) {
+            self.lexer.next(errs);
+            if self.is_curr(AnyNumber) {
+                Some(self.parse_number(errs)?)
+            } else {
+                None
+            }
+        } else {
+            Some(min)
+        };
+
+        let close = self.lexer.expect(TokenKind::CloseBrace, errs)?;
+        Ok((min, max, close))
+    }
+
+    fn parse_number(&mut self, errs: &mut Diagnostic) -> Result<usize, ()> {
+        let tok = self.lexer.expect(AnyNumber, errs)?;
+        let name = into_name(tok);
+        name.content
+            .parse()
+            .map_err(|_| errs.raise(ErrorKind::BadRepetition(name.span)))
+    }
+
+    fn parse_pattern_atom(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<PatternExpr, ()> {
+        let tok = self.lexer.check(PATTERN_START, errs)?;
+
+        if tok.kind == TokenKind::Null {
+            self.lexer.next(errs);
+            let kind = PatternExprKind::Null;
+            return Ok(PatternExpr { kind, span: tok.span });
+        }
+
+        match tok.kind {
+            TokenKind::Bang => {
+                self.lexer.next(errs);
+                let inner = self.parse_pattern_atom(errs)?;
+                let span = tok.span.join(&inner.span);
+                let kind = PatternExprKind::Not(Box::new(inner));
+                Ok(PatternExpr { kind, span })
+            },
+
+            TokenKind::OpenParen => {
+                self.lexer.next(errs);
+                let inner = self.parse_pattern(errs)?;
+                let close = self.lexer.expect(TokenKind::CloseParen, errs)?;
+                let span = tok.span.join(&close.span);
+                let kind = PatternExprKind::Group(Box::new(inner));
+                Ok(PatternExpr { kind, span })
+            },
+
+            TokenKind::OpenBracket => {
+                let (specs, span) = self.parse_matrix(errs)?;
+                let kind = PatternExprKind::Matrix(specs);
+                Ok(PatternExpr { kind, span })
+            },
+
+            TokenKind::ClassIdent(name) => {
+                self.lexer.next(errs);
+                let kind = PatternExprKind::Class(name);
+                Ok(PatternExpr { kind, span: tok.span })
+            },
+
+            TokenKind::Boundary => {
+                self.lexer.next(errs);
+                let kind = PatternExprKind::Boundary;
+                Ok(PatternExpr { kind, span: tok.span })
+            },
+
+            TokenKind::String(content) => {
+                self.lexer.next(errs);
+                let kind = PatternExprKind::Symbol(content);
+                Ok(PatternExpr { kind, span: tok.span })
+            },
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_matrix(
+        &mut self,
+        errs: &mut Diagnostic,
+    ) -> Result<(Vec<FeatureSpec>, Span), ()> {
+        let open = self.lexer.expect(TokenKind::OpenBracket, errs)?;
+        let mut specs = Vec::new();
+
+        loop {
+            let sign = self.lexer.expect(FEATURE_SIGNS, errs)?;
+            let name = self.parse_string(errs)?;
+            let span = sign.span.join(&name.span);
+            let value = sign.kind == TokenKind::Plus;
+            specs.push(FeatureSpec { value, name, span });
+
+            if !self.is_curr(FEATURE_SIGNS) {
+                break;
+            }
+        }
+
+        let close = self.lexer.expect(TokenKind::CloseBracket, errs)?;
+        Ok((specs, open.span.join(&close.span)))
+    }
+
+    fn parse_string(&mut self, errs: &mut Diagnostic) -> Result<Name, ()> {
+        let tok = self.lexer.expect(AnyString, errs)?;
+        Ok(into_name(tok))
+    }
+
+    fn parse_class_ident(&mut self, errs: &mut Diagnostic) -> Result<Name, ()> {
+        let tok = self.lexer.expect(AnyClassIdent, errs)?;
+        Ok(into_name(tok))
+    }
+}
+
+fn into_name(tok: Token) -> Name {
+    match tok.kind {
+        TokenKind::String(content) | TokenKind::ClassIdent(content) => {
+            Name { content, span: tok.span }
+        },
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Parser;
+    use crate::{
+        ast::{
+            BlockMode,
+            ClassExprKind,
+            Decl,
+            PatternExprKind,
+            ReplacementExprKind,
+            RuleModifierKind,
+        },
+        error::{Diagnostic, ErrorKind},
+        pat::CaptureName,
+        source::Src,
+    };
+
+    #[test]
+    fn alphabet_and_class() {
+        let src = Src::new(
+            "foo.psh",
+            "alphabet a, i, p, 'tʃ'\nclass \\V = a | i\nclass \\C = p | tʃ",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(program.decls.len(), 3);
+
+        match &program.decls[0] {
+            Decl::Alphabet(decl) => {
+                let symbols = decl
+                    .symbols
+                    .iter()
+                    .map(|name| &*name.content)
+                    .collect::<Vec<_>>();
+                assert_eq!(symbols, ["a", "i", "p", "tʃ"]);
+                assert_eq!(decl.span.as_str(), "alphabet a, i, p, 'tʃ'");
+            },
+            decl => panic!("expected alphabet, found {:?}", decl),
+        }
+
+        match &program.decls[1] {
+            Decl::Class(decl) => {
+                assert_eq!(decl.name.content, "V");
+                assert_eq!(decl.name.span.as_str(), "\\V");
+                assert_eq!(decl.span.as_str(), "class \\V = a | i");
+                match &decl.expr.kind {
+                    ClassExprKind::Or(left, right) => {
+                        assert_eq!(
+                            left.kind,
+                            ClassExprKind::Symbol("a".to_owned())
+                        );
+                        assert_eq!(
+                            right.kind,
+                            ClassExprKind::Symbol("i".to_owned())
+                        );
+                    },
+                    expr => panic!("expected alternative, found {:?}", expr),
+                }
+            },
+            decl => panic!("expected class, found {:?}", decl),
+        }
+    }
+
+    #[test]
+    fn nested_groups() {
+        let src = Src::new("foo.psh", "class \\X = (\\V | (p)) | a");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+
+        let decl = match &program.decls[0] {
+            Decl::Class(decl) => decl,
+            decl => panic!("expected class, found {:?}", decl),
+        };
+
+        let (left, right) = match &decl.expr.kind {
+            ClassExprKind::Or(left, right) => (left, right),
+            expr => panic!("expected alternative, found {:?}", expr),
+        };
+        assert_eq!(left.span.as_str(), "(\\V | (p))");
+        assert_eq!(right.kind, ClassExprKind::Symbol("a".to_owned()));
+
+        let group = match &left.kind {
+            ClassExprKind::Group(group) => group,
+            expr => panic!("expected group, found {:?}", expr),
+        };
+        match &group.kind {
+            ClassExprKind::Or(left, right) => {
+                assert_eq!(left.kind, ClassExprKind::Class("V".to_owned()));
+                assert_eq!(right.span.as_str(), "(p)");
+            },
+            expr => panic!("expected alternative, found {:?}", expr),
+        }
+    }
+
+    #[test]
+    fn rule_with_env() {
+        let src = Src::new("foo.psh", r"rule p > f / \V _ (a | i) \C");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+
+        let decl = match &program.decls[0] {
+            Decl::Rule(decl) => decl,
+            decl => panic!("expected rule, found {:?}", decl),
+        };
+        assert_eq!(decl.target.kind, PatternExprKind::Symbol("p".to_owned()));
+        assert_eq!(decl.replacement.len(), 1);
+        assert_eq!(
+            decl.replacement[0].kind,
+            ReplacementExprKind::Symbol("f".to_owned())
+        );
+
+        let env = decl.env.as_ref().unwrap();
+        assert_eq!(env.span.as_str(), r"/ \V _ (a | i) \C");
+        assert_eq!(
+            env.left.as_ref().unwrap().kind,
+            PatternExprKind::Class("V".to_owned())
+        );
+        match &env.right.as_ref().unwrap().kind {
+            PatternExprKind::And(left, right) => {
+                assert_eq!(left.span.as_str(), "(a | i)");
+                assert_eq!(right.kind, PatternExprKind::Class("C".to_owned()));
+            },
+            pat => panic!("expected sequence, found {:?}", pat),
+        }
+    }
+
+    #[test]
+    fn rules_without_env() {
+        let src =
+            Src::new("foo.psh", "rule p t > f\nrule a > e / _ i\nrule k > g");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(program.decls.len(), 3);
+
+        match &program.decls[0] {
+            Decl::Rule(decl) => {
+                assert_eq!(decl.target.span.as_str(), "p t");
+                assert!(decl.env.is_none());
+            },
+            decl => panic!("expected rule, found {:?}", decl),
+        }
+
+        match &program.decls[1] {
+            Decl::Rule(decl) => {
+                let env = decl.env.as_ref().unwrap();
+                assert!(env.left.is_none());
+                assert_eq!(env.right.as_ref().unwrap().span.as_str(), "i");
+            },
+            decl => panic!("expected rule, found {:?}", decl),
+        }
+    }
+
+    #[test]
+    fn null_symbols() {
+        let src = Src::new(
+            "foo.psh",
+            r"rule h > ∅
+            rule - > ə / \C _ \C - h
+            rule 0 > a / _ i{0}
+            rule ∅ > -",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(program.decls.len(), 4);
+
+        let rules = program
+            .decls
+            .iter()
+            .map(|decl| match decl {
+                Decl::Rule(decl) => decl,
+                decl => panic!("expected rule, found {:?}", decl),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(rules[0].replacement[0].kind, ReplacementExprKind::Null);
+        for rule in &rules[1 ..] {
+            assert_eq!(rule.target.kind, PatternExprKind::Null);
+        }
+        let env = rules[1].env.as_ref().unwrap();
+        assert!(matches!(
+            env.right.as_ref().unwrap().kind,
+            PatternExprKind::Diff(_, _)
+        ));
+        let env = rules[2].env.as_ref().unwrap();
+        assert!(matches!(
+            env.right.as_ref().unwrap().kind,
+            PatternExprKind::Repeat(_, 0, Some(0))
+        ));
+        assert_eq!(rules[3].replacement[0].kind, ReplacementExprKind::Null);
+    }
+
+    #[test]
+    fn null_aliases() {
+        // `-` is only null as the whole replacement.
+        let src = Src::new("foo.psh", "rule a > - b");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        assert!(parser.parse_program(&mut errs).is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::Expected(_, found) => {
+                assert_eq!(found.span.as_str(), "-")
+            },
+            kind => panic!("expected token error, found {:?}", kind),
+        }
+
+        let src = Src::new("foo.psh", "rule \\C - h > a / _ 0\nrule '0' > -");
+        let mut errs = Diagnostic::new();
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+        let rules = program
+            .decls
+            .iter()
+            .map(|decl| match decl {
+                Decl::Rule(decl) => decl,
+                decl => panic!("expected rule, found {:?}", decl),
+            })
+            .collect::<Vec<_>>();
+
+        // Inside a pattern, `-` is set difference and `0` an alphabet symbol.
+        assert!(matches!(rules[0].target.kind, PatternExprKind::Diff(_, _)));
+        let env = rules[0].env.as_ref().unwrap();
+        assert_eq!(
+            env.right.as_ref().unwrap().kind,
+            PatternExprKind::Symbol("0".to_owned())
+        );
+        assert_eq!(
+            rules[1].target.kind,
+            PatternExprKind::Symbol("0".to_owned())
+        );
+        assert_eq!(rules[1].replacement[0].kind, ReplacementExprKind::Null);
+    }
+
+    #[test]
+    fn metathesis() {
+        let src = Src::new("foo.psh", r"rule \C₁ \L₂ > \L₂ \C₁ / \V _");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let decl = match &program.decls[0] {
+            Decl::Rule(decl) => decl,
+            decl => panic!("expected rule, found {:?}", decl),
+        };
+        match &decl.target.kind {
+            PatternExprKind::And(left, right) => {
+                assert!(matches!(
+                    left.kind,
+                    PatternExprKind::Capture(_, CaptureName::Index(1))
+                ));
+                assert!(matches!(
+                    right.kind,
+                    PatternExprKind::Capture(_, CaptureName::Index(2))
+                ));
+            },
+            pat => panic!("expected sequence, found {:?}", pat),
+        }
+        let indices = decl
+            .replacement
+            .iter()
+            .map(|item| match &item.kind {
+                ReplacementExprKind::Ref(inner, name) => {
+                    (inner.span.as_str(), name.to_string())
+                },
+                kind => panic!("expected reference, found {:?}", kind),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(indices.len(), 2);
+        assert_eq!(indices[0], (r"\L", "₂".to_owned()));
+        assert_eq!(indices[1], (r"\C", "₁".to_owned()));
+    }
+
+    #[test]
+    fn repetitions() {
+        let src = Src::new(
+            "foo.psh",
+            r"rule p > f / \V \C* _ a? \C+ i{1,} u{2,3} o{2}",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+
+        let decl = match &program.decls[0] {
+            Decl::Rule(decl) => decl,
+            decl => panic!("expected rule, found {:?}", decl),
+        };
+        let env = decl.env.as_ref().unwrap();
+
+        match &env.left.as_ref().unwrap().kind {
+            PatternExprKind::And(_, right) => match &right.kind {
+                PatternExprKind::Star(inner) => {
+                    assert_eq!(right.span.as_str(), r"\C*");
+                    assert_eq!(
+                        inner.kind,
+                        PatternExprKind::Class("C".to_owned())
+                    );
+                },
+                pat => panic!("expected star, found {:?}", pat),
+            },
+            pat => panic!("expected sequence, found {:?}", pat),
+        }
+
+        let mut right = &env.right.as_ref().unwrap().kind;
+        let mut found = Vec::new();
+        while let PatternExprKind::And(left, last) = right {
+            found.push(&last.kind);
+            right = &left.kind;
+        }
+        found.push(right);
+        found.reverse();
+
+        assert_eq!(found.len(), 5);
+        assert!(matches!(found[0], PatternExprKind::Optional(_)));
+        assert!(matches!(found[1], PatternExprKind::Plus(_)));
+        assert!(matches!(found[2], PatternExprKind::Repeat(_, 1, None)));
+        assert!(matches!(found[3], PatternExprKind::Repeat(_, 2, Some(3))));
+        assert!(matches!(found[4], PatternExprKind::Repeat(_, 2, Some(2))));
+    }
+
+    #[test]
+    fn bad_repetition() {
+        let src = Src::new("foo.psh", "rule p{3,1} > f\nrule p{x} > f");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+
+        assert!(parser.parse_program(&mut errs).is_err());
+        assert_eq!(errs.as_slice().len(), 2);
+    }
+
+    #[test]
+    fn negation_and_difference() {
+        let src = Src::new(
+            "foo.psh",
+            r"class \X = !\V | \C - (p | b) - t
+            rule p > f / !\V _ \C - p",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+
+        let decl = match &program.decls[0] {
+            Decl::Class(decl) => decl,
+            decl => panic!("expected class, found {:?}", decl),
+        };
+        let (left, right) = match &decl.expr.kind {
+            ClassExprKind::Or(left, right) => (left, right),
+            expr => panic!("expected alternative, found {:?}", expr),
+        };
+        match &left.kind {
+            ClassExprKind::Not(inner) => {
+                assert_eq!(inner.kind, ClassExprKind::Class("V".to_owned()))
+            },
+            expr => panic!("expected negation, found {:?}", expr),
+        }
+        match &right.kind {
+            ClassExprKind::Diff(left, last) => {
+                assert_eq!(left.span.as_str(), r"\C - (p | b)");
+                assert_eq!(last.kind, ClassExprKind::Symbol("t".to_owned()));
+            },
+            expr => panic!("expected difference, found {:?}", expr),
+        }
+
+        let decl = match &program.decls[1] {
+            Decl::Rule(decl) => decl,
+            decl => panic!("expected rule, found {:?}", decl),
+        };
+        let env = decl.env.as_ref().unwrap();
+        assert!(matches!(
+            env.left.as_ref().unwrap().kind,
+            PatternExprKind::Not(_)
+        ));
+        assert!(matches!(
+            env.right.as_ref().unwrap().kind,
+            PatternExprKind::Diff(_, _)
+        ));
+    }
+
+    #[test]
+    fn rule_blocks() {
+        let src = Src::new(
+            "foo.psh",
+            "rule a > e\nsimultaneous {\n    rule a > e\n    rule e > i\n}\n\
+             iterative { rule a t > t a }",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(program.decls.len(), 3);
+        assert!(matches!(program.decls[0], Decl::Rule(_)));
+        match &program.decls[1] {
+            Decl::Block(block) => {
+                assert_eq!(block.mode, BlockMode::Simultaneous);
+                assert_eq!(block.rules.len(), 2);
+            },
+            decl => panic!("expected block, found {:?}", decl),
+        }
+        match &program.decls[2] {
+            Decl::Block(block) => {
+                assert_eq!(block.mode, BlockMode::Iterative);
+                assert_eq!(block.rules.len(), 1);
+            },
+            decl => panic!("expected block, found {:?}", decl),
+        }
+    }
+
+    #[test]
+    fn block_recovery() {
+        let src = Src::new(
+            "foo.psh",
+            "simultaneous {\n    rule a{2} >\n    rule e > i\n}\nrule i > u",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        assert!(parser.parse_program(&mut errs).is_err());
+
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::Expected(_, found) => {
+                assert_eq!(found.span.as_str(), "rule")
+            },
+            kind => panic!("expected token error, found {:?}", kind),
+        }
+        assert!(parser.lexer().is_eof());
+    }
+
+    #[test]
+    fn rule_modifiers() {
+        let src =
+            Src::new("foo.psh", "rule rtl feeding a > e / _ e\nrule ltr a > e");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let modifiers = program
+            .decls
+            .iter()
+            .map(|decl| match decl {
+                Decl::Rule(decl) => decl
+                    .modifiers
+                    .iter()
+                    .map(|modifier| modifier.kind)
+                    .collect::<Vec<_>>(),
+                decl => panic!("expected rule, found {:?}", decl),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            modifiers,
+            [
+                vec![RuleModifierKind::Rtl, RuleModifierKind::Feeding],
+                vec![RuleModifierKind::Ltr]
+            ]
+        );
+    }
+
+    #[test]
+    fn feature_matrix() {
+        let src = Src::new(
+            "foo.psh",
+            "class \\S = [+stop -voice]\nrule [+nasal] > ∅ / _ [-syllabic]",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let specs = match &program.decls[0] {
+            Decl::Class(decl) => match &decl.expr.kind {
+                ClassExprKind::Matrix(specs) => specs,
+                expr => panic!("expected matrix, found {:?}", expr),
+            },
+            decl => panic!("expected class, found {:?}", decl),
+        };
+        let specs = specs
+            .iter()
+            .map(|spec| (spec.value, spec.name.content.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(specs, [(true, "stop"), (false, "voice")]);
+
+        match &program.decls[1] {
+            Decl::Rule(decl) => {
+                assert!(matches!(decl.target.kind, PatternExprKind::Matrix(_)));
+                assert_eq!(decl.target.span.as_str(), "[+nasal]");
+            },
+            decl => panic!("expected rule, found {:?}", decl),
+        }
+    }
+
+    #[test]
+    fn feature_decl() {
+        let src = Src::new(
+            "foo.psh",
+            "feature voice = +(b | d) -(p | t)\nfeature height = high i low a",
+        );
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+        let program = parser.parse_program(&mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let values = program
+            .decls
+            .iter()
+            .map(|decl| match decl {
+                Decl::Feature(decl) => decl
+                    .values
+                    .iter()
+                    .map(|value| {
+                        (
+                            value.value.content.as_str(),
+                            value.members.span.as_str(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+                decl => panic!("expected feature, found {:?}", decl),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                [("+", "(b | d)"), ("-", "(p | t)")],
+                [("high", "i"), ("low", "a")],
+            ]
+        );
+    }
+
+    #[test]
+    fn error_recovery() {
+        let src =
+            Src::new("foo.psh", "class \\V a | i\nalphabet a,\nclass \\C = p");
+        let mut errs = Diagnostic::new();
+
+        let mut parser = Parser::new(src.reader(), &mut errs);
+
+        assert!(parser.parse_program(&mut errs).is_err());
+        assert_eq!(errs.as_slice().len(), 2);
+        assert!(parser.lexer().is_eof());
+    }
+}
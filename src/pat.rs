@@ -1,4 +1,41 @@
-use crate::symbol::{NonTerminal, Terminal};
+use crate::{
+    feature::Matrix,
+    symbol::{NonTerminal, Terminal},
+};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CaptureName {
+    Index(usize),
+    Name(String),
+}
+
+impl fmt::Display for CaptureName {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureName::Index(index) => {
+                for digit in index.to_string().chars() {
+                    let offset = digit as u32 - '0' as u32;
+                    fmt.write_char(
+                        char::from_u32('₀' as u32 + offset).unwrap(),
+                    )?;
+                }
+                Ok(())
+            },
+            CaptureName::Name(name) => write!(fmt, "@{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Capture {
+    pub name: CaptureName,
+    pub segment: MatchSegment,
+    pub terms: Vec<Terminal>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MatchSegment {
@@ -9,21 +46,37 @@ pub struct MatchSegment {
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct Match {
     pub segments: Vec<MatchSegment>,
+    pub captures: Vec<Capture>,
 }
 
 impl Match {
+    pub fn new(segments: Vec<MatchSegment>) -> Self {
+        Self { segments, captures: Vec::new() }
+    }
+
+    pub fn captures(&self) -> &[Capture] {
+        &self.captures
+    }
+
+    pub fn capture(&self, name: &CaptureName) -> Option<&Capture> {
+        find_capture(&self.captures, name)
+    }
+
     pub fn matched(&self) -> bool {
-        self.segments.len() > 0
+        !self.segments.is_empty()
     }
 
     pub fn unmatched(&self) -> bool {
-        self.segments.len() == 0
+        self.segments.is_empty()
     }
 
     pub fn add_offset(&mut self, offset: usize) {
         for seg in &mut self.segments {
             seg.start += offset;
         }
+        for capture in &mut self.captures {
+            capture.segment.start += offset;
+        }
     }
 
     pub fn general_start(&self) -> usize {
@@ -52,7 +105,7 @@ impl Match {
         F: FnOnce(&Self) -> Match,
     {
         if self.matched() {
-            let mut other = right(&self);
+            let mut other = right(self);
             if other.unmatched() {
                 *self = other;
             } else if self.general_end() == other.general_start() {
@@ -60,22 +113,176 @@ impl Match {
                 let last = self.segments.len() - 1;
                 self.segments[last].len += first.len;
                 self.segments.append(&mut other.segments);
+                self.captures.append(&mut other.captures);
             }
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum MatchMode {
+    #[default]
+    Longest,
+    First,
+}
+
+impl MatchMode {
+    fn select(self, matches: Vec<Match>) -> Match {
+        let mut matches = matches.into_iter();
+        match self {
+            MatchMode::First => matches.next().unwrap_or_default(),
+            MatchMode::Longest => matches
+                .fold(None, |best: Option<Match>, curr| match best {
+                    Some(best) if best.general_end() >= curr.general_end() => {
+                        Some(best)
+                    },
+                    _ => Some(curr),
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Pattern {
     Terms(Vec<Terminal>),
     NonTerm(NonTerminal),
+    Matrix(Matrix),
+    Start,
+    End,
+    Optional(Box<Pattern>),
+    Star(Box<Pattern>),
+    Plus(Box<Pattern>),
+    Repeat(Box<Pattern>, usize, Option<usize>),
+    Capture(CaptureName, Box<Pattern>),
     And(Box<Pattern>, Box<Pattern>),
     Or(Box<Pattern>, Box<Pattern>),
 }
 
 impl Pattern {
     pub fn match_terms(&self, terms: &[Terminal]) -> Match {
-        match_pattern(self, terms, 0)
+        self.match_at(terms, 0)
+    }
+
+    pub fn match_at(&self, terms: &[Terminal], offset: usize) -> Match {
+        self.match_at_with(terms, offset, MatchMode::default())
+    }
+
+    pub fn match_at_with(
+        &self,
+        terms: &[Terminal],
+        offset: usize,
+        mode: MatchMode,
+    ) -> Match {
+        mode.select(self.match_all_at(terms, offset))
+    }
+
+    pub fn match_all_at(
+        &self,
+        terms: &[Terminal],
+        offset: usize,
+    ) -> Vec<Match> {
+        self.match_all_bound(terms, offset, &[])
+    }
+
+    // Captures in `bound` were made by an earlier match, such as the left
+    // environment of a rule, and turn their names into back-references.
+    pub fn match_all_bound(
+        &self,
+        terms: &[Terminal],
+        offset: usize,
+        bound: &[Capture],
+    ) -> Vec<Match> {
+        if offset > terms.len() {
+            Vec::new()
+        } else {
+            match_pattern(self, terms, offset, bound)
+        }
+    }
+
+    pub fn find(&self, terms: &[Terminal]) -> Match {
+        self.find_at(terms, 0)
+    }
+
+    pub fn find_at(&self, terms: &[Terminal], start: usize) -> Match {
+        self.find_at_with(terms, start, MatchMode::default())
+    }
+
+    pub fn find_at_with(
+        &self,
+        terms: &[Terminal],
+        start: usize,
+        mode: MatchMode,
+    ) -> Match {
+        (start ..= terms.len())
+            .map(|offset| self.match_at_with(terms, offset, mode))
+            .find(Match::matched)
+            .unwrap_or_default()
+    }
+
+    pub fn find_iter<'pat, 'terms>(
+        &'pat self,
+        terms: &'terms [Terminal],
+    ) -> Matches<'pat, 'terms> {
+        Matches {
+            pat: self,
+            terms,
+            pos: 0,
+            overlapping: false,
+            mode: MatchMode::default(),
+        }
+    }
+
+    pub fn find_overlapping_iter<'pat, 'terms>(
+        &'pat self,
+        terms: &'terms [Terminal],
+    ) -> Matches<'pat, 'terms> {
+        Matches {
+            pat: self,
+            terms,
+            pos: 0,
+            overlapping: true,
+            mode: MatchMode::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Matches<'pat, 'terms> {
+    pat: &'pat Pattern,
+    terms: &'terms [Terminal],
+    pos: usize,
+    overlapping: bool,
+    mode: MatchMode,
+}
+
+impl<'pat, 'terms> Matches<'pat, 'terms> {
+    pub fn with_mode(self, mode: MatchMode) -> Self {
+        Self { mode, ..self }
+    }
+}
+
+impl<'pat, 'terms> Iterator for Matches<'pat, 'terms> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos > self.terms.len() {
+            return None;
+        }
+
+        let found = self.pat.find_at_with(self.terms, self.pos, self.mode);
+        if found.unmatched() {
+            self.pos = self.terms.len() + 1;
+            return None;
+        }
+
+        self.pos = if self.overlapping || found.general_len() == 0 {
+            found.general_start() + 1
+        } else {
+            found.general_end()
+        };
+
+        Some(found)
     }
 }
 
@@ -83,13 +290,12 @@ fn match_term_pat(
     pat: &[Terminal],
     terms: &[Terminal],
     offset: usize,
-) -> Match {
-    Match {
-        segments: if terms[offset ..].starts_with(&*pat) {
-            vec![MatchSegment { start: offset, len: pat.len() }]
-        } else {
-            vec![]
-        },
+) -> Vec<Match> {
+    if terms[offset ..].starts_with(pat) {
+        let segments = vec![MatchSegment { start: offset, len: pat.len() }];
+        vec![Match::new(segments)]
+    } else {
+        vec![]
     }
 }
 
@@ -97,16 +303,99 @@ fn match_non_term_pat(
     non_term: &NonTerminal,
     terms: &[Terminal],
     offset: usize,
-) -> Match {
-    Match {
-        segments: if terms.len() == offset + 1
-            && non_term.contains(&terms[offset])
-        {
-            vec![MatchSegment { start: offset, len: 1 }]
-        } else {
-            vec![]
+) -> Vec<Match> {
+    non_term
+        .prefix_lens(&terms[offset ..])
+        .into_iter()
+        .map(|len| Match::new(vec![MatchSegment { start: offset, len }]))
+        .collect()
+}
+
+fn match_matrix_pat(
+    matrix: &Matrix,
+    terms: &[Terminal],
+    offset: usize,
+) -> Vec<Match> {
+    match terms.get(offset) {
+        Some(term) if matrix.matches(term) => {
+            vec![Match::new(vec![MatchSegment { start: offset, len: 1 }])]
         },
+        _ => vec![],
+    }
+}
+
+fn match_boundary_pat(at_boundary: bool, offset: usize) -> Vec<Match> {
+    if at_boundary {
+        vec![Match::new(vec![MatchSegment { start: offset, len: 0 }])]
+    } else {
+        vec![]
+    }
+}
+
+fn match_repeat_pat(
+    pat: &Pattern,
+    min: usize,
+    max: Option<usize>,
+    terms: &[Terminal],
+    offset: usize,
+    bound: &[Capture],
+) -> Vec<Match> {
+    let empty = Match::new(vec![MatchSegment { start: offset, len: 0 }]);
+    let mut frontier = vec![empty];
+    let mut by_count = Vec::new();
+    let mut count = 0;
+
+    while !frontier.is_empty() {
+        if count >= min {
+            by_count.push(frontier.clone());
+        }
+        if max.is_some_and(|max| count >= max) {
+            break;
+        }
+
+        let mut next = Vec::new();
+        for prev in &frontier {
+            let bound = with_captures(bound, prev);
+            for found in match_pattern(pat, terms, prev.general_end(), &bound) {
+                if count < min || found.general_len() > 0 {
+                    let mut combined = prev.clone();
+                    combined.append(|_| found);
+                    next.push(combined);
+                }
+            }
+        }
+
+        frontier = dedup(next);
+        count += 1;
+    }
+
+    dedup(by_count.into_iter().rev().flatten().collect())
+}
+
+// A name which is already bound refers back to the earlier match. Rules never
+// repeat a capture, so this is only the case for the same name used twice.
+fn match_capture_pat(
+    name: &CaptureName,
+    pat: &Pattern,
+    terms: &[Terminal],
+    offset: usize,
+    bound: &[Capture],
+) -> Vec<Match> {
+    if let Some(prev) = find_capture(bound, name) {
+        return match_term_pat(&prev.terms, terms, offset);
     }
+
+    let mut matches = match_pattern(pat, terms, offset, bound);
+    for found in &mut matches {
+        let start = found.general_start();
+        let end = found.general_end();
+        found.captures.push(Capture {
+            name: name.clone(),
+            segment: MatchSegment { start, len: end - start },
+            terms: terms[start .. end].to_vec(),
+        });
+    }
+    matches
 }
 
 fn match_and_pat(
@@ -114,10 +403,21 @@ fn match_and_pat(
     right: &Pattern,
     terms: &[Terminal],
     offset: usize,
-) -> Match {
-    let mut lmatch = match_pattern(left, terms, offset);
-    lmatch.append(|lmatch| match_pattern(right, terms, lmatch.general_end()));
-    lmatch
+    bound: &[Capture],
+) -> Vec<Match> {
+    let mut matches = Vec::new();
+
+    for lmatch in match_pattern(left, terms, offset, bound) {
+        let bound = with_captures(bound, &lmatch);
+        let end = lmatch.general_end();
+        for rmatch in match_pattern(right, terms, end, &bound) {
+            let mut combined = lmatch.clone();
+            combined.append(|_| rmatch);
+            matches.push(combined);
+        }
+    }
+
+    dedup(matches)
 }
 
 fn match_or_pat(
@@ -125,16 +425,40 @@ fn match_or_pat(
     right: &Pattern,
     terms: &[Terminal],
     offset: usize,
-) -> Match {
-    let lmatch = match_pattern(left, terms, offset);
-    if lmatch.matched() {
-        lmatch
-    } else {
-        match_pattern(right, terms, offset)
-    }
+    bound: &[Capture],
+) -> Vec<Match> {
+    let mut matches = match_pattern(left, terms, offset, bound);
+    matches.append(&mut match_pattern(right, terms, offset, bound));
+    dedup(matches)
 }
 
-fn match_pattern(pat: &Pattern, terms: &[Terminal], offset: usize) -> Match {
+// Different paths through a pattern often end up with the same match, as in
+// `(a|a)*`. Keeping each match only once, in the order it was first found,
+// stops those paths from multiplying at every step.
+fn dedup(matches: Vec<Match>) -> Vec<Match> {
+    let mut seen = BTreeSet::new();
+    matches.into_iter().filter(|found| seen.insert(found.clone())).collect()
+}
+
+fn find_capture<'caps>(
+    captures: &'caps [Capture],
+    name: &CaptureName,
+) -> Option<&'caps Capture> {
+    captures.iter().rev().find(|capture| capture.name == *name)
+}
+
+fn with_captures(bound: &[Capture], found: &Match) -> Vec<Capture> {
+    let mut bound = bound.to_vec();
+    bound.extend_from_slice(&found.captures);
+    bound
+}
+
+fn match_pattern(
+    pat: &Pattern,
+    terms: &[Terminal],
+    offset: usize,
+    bound: &[Capture],
+) -> Vec<Match> {
     match pat {
         Pattern::Terms(test) => match_term_pat(test, terms, offset),
 
@@ -142,19 +466,58 @@ fn match_pattern(pat: &Pattern, terms: &[Terminal], offset: usize) -> Match {
             match_non_term_pat(non_term, terms, offset)
         },
 
-        Pattern::And(left, right) => match_and_pat(left, right, terms, offset),
+        Pattern::Matrix(matrix) => match_matrix_pat(matrix, terms, offset),
+
+        Pattern::Start => match_boundary_pat(offset == 0, offset),
+
+        Pattern::End => match_boundary_pat(offset == terms.len(), offset),
 
-        Pattern::Or(left, right) => match_or_pat(left, right, terms, offset),
+        Pattern::Optional(inner) => {
+            match_repeat_pat(inner, 0, Some(1), terms, offset, bound)
+        },
+
+        Pattern::Star(inner) => {
+            match_repeat_pat(inner, 0, None, terms, offset, bound)
+        },
+
+        Pattern::Plus(inner) => {
+            match_repeat_pat(inner, 1, None, terms, offset, bound)
+        },
+
+        Pattern::Repeat(inner, min, max) => {
+            match_repeat_pat(inner, *min, *max, terms, offset, bound)
+        },
+
+        Pattern::Capture(name, inner) => {
+            match_capture_pat(name, inner, terms, offset, bound)
+        },
+
+        Pattern::And(left, right) => {
+            match_and_pat(left, right, terms, offset, bound)
+        },
+
+        Pattern::Or(left, right) => {
+            match_or_pat(left, right, terms, offset, bound)
+        },
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Match, MatchSegment, NonTerminal, Pattern, Terminal};
+    use super::{
+        Capture,
+        CaptureName,
+        Match,
+        MatchMode,
+        MatchSegment,
+        NonTerminal,
+        Pattern,
+        Terminal,
+    };
     use crate::{
         find_syms,
         make_terms,
-        symbol::{Symbol, Table},
+        symbol::{Sequence, Symbol, Table},
     };
 
     fn terms_for_test() -> Table<Terminal> {
@@ -185,7 +548,7 @@ mod test {
 
         assert_eq!(
             pat.match_terms(&string1),
-            Match { segments: vec![MatchSegment { start: 0, len: 3 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 3 }])
         );
         assert_eq!(pat.match_terms(&string2), Match::default());
     }
@@ -198,19 +561,72 @@ mod test {
         let string2 = find_syms!(&terms, Clone::clone, "i").unwrap();
         let string3 = find_syms!(&terms, Clone::clone, "i", "a").unwrap();
         let string4 = find_syms!(&terms, Clone::clone, "p").unwrap();
+        let string5 =
+            find_syms!(&terms, Clone::clone, "p", "a", "q", "u").unwrap();
 
         let pat = Pattern::NonTerm(non_terms.find("V").unwrap().clone());
 
         assert_eq!(
             pat.match_terms(&string1),
-            Match { segments: vec![MatchSegment { start: 0, len: 1 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 1 }])
         );
         assert_eq!(
             pat.match_terms(&string2),
-            Match { segments: vec![MatchSegment { start: 0, len: 1 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 1 }])
+        );
+        assert_eq!(
+            pat.match_terms(&string3),
+            Match::new(vec![MatchSegment { start: 0, len: 1 }])
+        );
+        assert_eq!(
+            pat.match_at(&string3, 1),
+            Match::new(vec![MatchSegment { start: 1, len: 1 }])
         );
-        assert_eq!(pat.match_terms(&string3), Match::default());
+        assert_eq!(pat.match_at(&string3, 2), Match::default());
         assert_eq!(pat.match_terms(&string4), Match::default());
+        assert_eq!(
+            pat.find(&string5),
+            Match::new(vec![MatchSegment { start: 1, len: 1 }])
+        );
+        assert_eq!(
+            pat.find_iter(&string5).collect::<Vec<_>>(),
+            vec![
+                Match::new(vec![MatchSegment { start: 1, len: 1 }]),
+                Match::new(vec![MatchSegment { start: 3, len: 1 }]),
+            ]
+        );
+    }
+
+    #[test]
+    fn non_term_seq_member() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 = find_syms!(&terms, Clone::clone, "p", "c").unwrap();
+        let string2 =
+            find_syms!(&terms, Clone::clone, "a", "p", "c", "a").unwrap();
+        let string3 = find_syms!(&terms, Clone::clone, "a", "q", "a").unwrap();
+
+        let members = vec![
+            Symbol::Seq(Sequence::new(string1)),
+            Symbol::NonTerm(non_terms.find("V").unwrap().clone()),
+            Symbol::Term(terms.find("q").unwrap().clone()),
+        ];
+        let pat = Pattern::NonTerm(NonTerminal::new("X", members));
+
+        assert_eq!(
+            pat.match_at(&string2, 1),
+            Match::new(vec![MatchSegment { start: 1, len: 2 }])
+        );
+        assert_eq!(
+            pat.find_iter(&string2).collect::<Vec<_>>(),
+            vec![
+                Match::new(vec![MatchSegment { start: 0, len: 1 }]),
+                Match::new(vec![MatchSegment { start: 1, len: 2 }]),
+                Match::new(vec![MatchSegment { start: 3, len: 1 }]),
+            ]
+        );
+        assert_eq!(pat.find_iter(&string3).count(), 3);
+        assert_eq!(pat.match_at(&string2, 2), Match::default());
     }
 
     #[test]
@@ -230,11 +646,97 @@ mod test {
         assert_eq!(pat.match_terms(&string2), Match::default());
         assert_eq!(
             pat.match_terms(&string3),
-            Match { segments: vec![MatchSegment { start: 0, len: 3 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 3 }])
         );
         assert_eq!(pat.match_terms(&string4), Match::default());
     }
 
+    #[test]
+    fn capture_pat() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 =
+            find_syms!(&terms, Clone::clone, "i", "p", "a", "c").unwrap();
+
+        let consonant = non_terms.find("C").unwrap().clone();
+        let vowel = non_terms.find("V").unwrap().clone();
+        let left = Pattern::Capture(
+            CaptureName::Index(1),
+            Box::new(Pattern::NonTerm(consonant)),
+        );
+        let right = Pattern::Capture(
+            CaptureName::Name("v".to_owned()),
+            Box::new(Pattern::Plus(Box::new(Pattern::NonTerm(vowel)))),
+        );
+        let pat = Pattern::And(Box::new(left), Box::new(right));
+
+        let found = pat.find(&string1);
+        assert_eq!(found.segments, [MatchSegment { start: 1, len: 2 }]);
+        assert_eq!(found.captures().len(), 2);
+
+        let first = found.capture(&CaptureName::Index(1)).unwrap();
+        assert_eq!(first.segment, MatchSegment { start: 1, len: 1 });
+        assert_eq!(first.terms, &string1[1 .. 2]);
+
+        let second = found.capture(&CaptureName::Name("v".to_owned())).unwrap();
+        assert_eq!(second.segment, MatchSegment { start: 2, len: 1 });
+        assert_eq!(second.terms, &string1[2 .. 3]);
+
+        assert_eq!(found.capture(&CaptureName::Index(2)), None);
+    }
+
+    #[test]
+    fn back_reference() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 =
+            find_syms!(&terms, Clone::clone, "p", "a", "c", "c", "u").unwrap();
+        let string2 = find_syms!(&terms, Clone::clone, "p", "p", "q").unwrap();
+
+        let name = CaptureName::Index(1);
+        let consonant = Pattern::Capture(
+            name.clone(),
+            Box::new(Pattern::NonTerm(non_terms.find("C").unwrap().clone())),
+        );
+        let geminate =
+            Pattern::And(Box::new(consonant.clone()), Box::new(consonant));
+
+        assert_eq!(
+            geminate.find(&string1),
+            Match {
+                segments: vec![MatchSegment { start: 2, len: 2 }],
+                captures: vec![Capture {
+                    name: name.clone(),
+                    segment: MatchSegment { start: 2, len: 1 },
+                    terms: string1[2 .. 3].to_vec(),
+                }],
+            }
+        );
+        assert_eq!(
+            geminate.find(&string2),
+            Match {
+                segments: vec![MatchSegment { start: 0, len: 2 }],
+                captures: vec![Capture {
+                    name: name.clone(),
+                    segment: MatchSegment { start: 0, len: 1 },
+                    terms: string2[0 .. 1].to_vec(),
+                }],
+            }
+        );
+
+        // Captures bound by an earlier match turn the pattern into a
+        // back-reference from the start.
+        let bound = geminate.find(&string1).captures;
+        let single = geminate.match_all_bound(&string2, 0, &bound);
+        assert!(single.is_empty());
+        let single = Pattern::Capture(name, Box::new(Pattern::Terms(vec![])))
+            .match_all_bound(&string1, 3, &bound);
+        assert_eq!(
+            single,
+            [Match::new(vec![MatchSegment { start: 3, len: 1 }])]
+        );
+    }
+
     #[test]
     fn or_pat() {
         let terms = terms_for_test();
@@ -250,16 +752,308 @@ mod test {
 
         assert_eq!(
             pat.match_terms(&string1),
-            Match { segments: vec![MatchSegment { start: 0, len: 2 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
         );
         assert_eq!(pat.match_terms(&string2), Match::default());
         assert_eq!(
             pat.match_terms(&string3),
-            Match { segments: vec![MatchSegment { start: 0, len: 2 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
         );
         assert_eq!(
             pat.match_terms(&string4),
-            Match { segments: vec![MatchSegment { start: 0, len: 1 }] }
+            Match::new(vec![MatchSegment { start: 0, len: 1 }])
+        );
+    }
+
+    #[test]
+    fn find_unanchored() {
+        let terms = terms_for_test();
+        let string1 = find_syms!(&terms, Clone::clone, "c", "u").unwrap();
+        let string2 =
+            find_syms!(&terms, Clone::clone, "a", "c", "u", "p", "c", "u")
+                .unwrap();
+        let string3 = find_syms!(&terms, Clone::clone, "c", "a", "u").unwrap();
+
+        let pat = Pattern::Terms(string1);
+
+        assert_eq!(
+            pat.find(&string2),
+            Match::new(vec![MatchSegment { start: 1, len: 2 }])
+        );
+        assert_eq!(
+            pat.find_at(&string2, 2),
+            Match::new(vec![MatchSegment { start: 4, len: 2 }])
+        );
+        assert_eq!(pat.find_at(&string2, 5), Match::default());
+        assert_eq!(pat.find_at(&string2, 10), Match::default());
+        assert_eq!(pat.find(&string3), Match::default());
+    }
+
+    #[test]
+    fn find_iter() {
+        let terms = terms_for_test();
+        let string1 = find_syms!(&terms, Clone::clone, "a", "a").unwrap();
+        let string2 =
+            find_syms!(&terms, Clone::clone, "a", "a", "a", "p", "a", "a")
+                .unwrap();
+
+        let pat = Pattern::Terms(string1);
+
+        assert_eq!(
+            pat.find_iter(&string2).collect::<Vec<_>>(),
+            vec![
+                Match::new(vec![MatchSegment { start: 0, len: 2 }]),
+                Match::new(vec![MatchSegment { start: 4, len: 2 }]),
+            ]
+        );
+        assert_eq!(
+            pat.find_overlapping_iter(&string2).collect::<Vec<_>>(),
+            vec![
+                Match::new(vec![MatchSegment { start: 0, len: 2 }]),
+                Match::new(vec![MatchSegment { start: 1, len: 2 }]),
+                Match::new(vec![MatchSegment { start: 4, len: 2 }]),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_iter_empty() {
+        let terms = terms_for_test();
+        let string1 = find_syms!(&terms, Clone::clone, "a", "p").unwrap();
+
+        let pat = Pattern::Terms(Vec::new());
+
+        assert_eq!(
+            pat.find_iter(&string1).collect::<Vec<_>>(),
+            vec![
+                Match::new(vec![MatchSegment { start: 0, len: 0 }]),
+                Match::new(vec![MatchSegment { start: 1, len: 0 }]),
+                Match::new(vec![MatchSegment { start: 2, len: 0 }]),
+            ]
+        );
+    }
+
+    #[test]
+    fn backtracking_or_in_and() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 = find_syms!(&terms, Clone::clone, "a").unwrap();
+        let string2 = find_syms!(&terms, Clone::clone, "a", "p").unwrap();
+        let string3 = find_syms!(&terms, Clone::clone, "a", "p", "c").unwrap();
+        let string4 = find_syms!(&terms, Clone::clone, "a", "c").unwrap();
+
+        let short = Pattern::Terms(string1);
+        let long = Pattern::Terms(string2.clone());
+        let alt = Pattern::Or(Box::new(short), Box::new(long));
+        let right = Pattern::NonTerm(non_terms.find("C").unwrap().clone());
+        let pat = Pattern::And(Box::new(alt.clone()), Box::new(right));
+
+        assert_eq!(
+            pat.match_terms(&string3),
+            Match::new(vec![MatchSegment { start: 0, len: 3 }])
+        );
+        assert_eq!(
+            pat.match_at_with(&string3, 0, MatchMode::First),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+        assert_eq!(
+            pat.match_terms(&string4),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+        assert_eq!(
+            pat.match_terms(&string2),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+
+        assert_eq!(
+            alt.match_terms(&string2),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+        assert_eq!(
+            alt.match_at_with(&string2, 0, MatchMode::First),
+            Match::new(vec![MatchSegment { start: 0, len: 1 }])
+        );
+    }
+
+    #[test]
+    fn ambiguous_class_alternation() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 = find_syms!(&terms, Clone::clone, "q").unwrap();
+        let string2 = find_syms!(&terms, Clone::clone, "q", "i").unwrap();
+        let string3 = find_syms!(&terms, Clone::clone, "q", "i", "u").unwrap();
+
+        let vowel = Pattern::NonTerm(non_terms.find("V").unwrap().clone());
+        let cons = Pattern::NonTerm(non_terms.find("C").unwrap().clone());
+        let q = Pattern::Terms(string1.clone());
+        let qi = Pattern::Terms(string2);
+        let alt = Pattern::Or(
+            Box::new(Pattern::Or(Box::new(cons), Box::new(q))),
+            Box::new(qi),
+        );
+        let pat = Pattern::And(Box::new(alt.clone()), Box::new(vowel));
+
+        assert_eq!(
+            pat.match_terms(&string3),
+            Match::new(vec![MatchSegment { start: 0, len: 3 }])
+        );
+        assert_eq!(
+            alt.match_terms(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 1 }])
+        );
+        assert_eq!(
+            pat.find_iter(&string3).with_mode(MatchMode::First).next(),
+            Some(Match::new(vec![MatchSegment { start: 0, len: 2 }]))
+        );
+    }
+
+    #[test]
+    fn boundary_pat() {
+        let terms = terms_for_test();
+        let string1 = find_syms!(&terms, Clone::clone, "p", "a").unwrap();
+        let string2 = find_syms!(&terms, Clone::clone, "a").unwrap();
+
+        let start = Pattern::And(
+            Box::new(Pattern::Start),
+            Box::new(Pattern::Terms(string1.clone())),
+        );
+        let end = Pattern::And(
+            Box::new(Pattern::Terms(string2)),
+            Box::new(Pattern::End),
+        );
+
+        assert_eq!(
+            start.find(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+        assert_eq!(start.find_at(&string1, 1), Match::default());
+        assert_eq!(
+            end.find(&string1),
+            Match::new(vec![MatchSegment { start: 1, len: 1 }])
+        );
+        assert_eq!(
+            Pattern::End.find(&string1),
+            Match::new(vec![MatchSegment { start: 2, len: 0 }])
+        );
+    }
+
+    #[test]
+    fn repeat_pat() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 =
+            find_syms!(&terms, Clone::clone, "a", "p", "c", "q", "i").unwrap();
+        let string2 = find_syms!(&terms, Clone::clone, "a", "i").unwrap();
+
+        let vowel = Pattern::NonTerm(non_terms.find("V").unwrap().clone());
+        let cons = Pattern::NonTerm(non_terms.find("C").unwrap().clone());
+        let around = |middle: Pattern| {
+            Pattern::And(
+                Box::new(Pattern::And(
+                    Box::new(vowel.clone()),
+                    Box::new(middle),
+                )),
+                Box::new(vowel.clone()),
+            )
+        };
+
+        let star = around(Pattern::Star(Box::new(cons.clone())));
+        assert_eq!(
+            star.match_terms(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 5 }])
+        );
+        assert_eq!(
+            star.match_terms(&string2),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+
+        let plus = around(Pattern::Plus(Box::new(cons.clone())));
+        assert_eq!(
+            plus.match_terms(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 5 }])
+        );
+        assert_eq!(plus.match_terms(&string2), Match::default());
+
+        let optional = around(Pattern::Optional(Box::new(cons.clone())));
+        assert_eq!(optional.match_terms(&string1), Match::default());
+        assert_eq!(
+            optional.match_terms(&string2),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+
+        let bounded =
+            around(Pattern::Repeat(Box::new(cons.clone()), 1, Some(2)));
+        assert_eq!(bounded.match_terms(&string1), Match::default());
+        let bounded =
+            around(Pattern::Repeat(Box::new(cons.clone()), 2, Some(3)));
+        assert_eq!(
+            bounded.match_terms(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 5 }])
+        );
+        let exact = Pattern::Repeat(Box::new(cons), 2, Some(2));
+        assert_eq!(
+            exact.find(&string1),
+            Match::new(vec![MatchSegment { start: 1, len: 2 }])
+        );
+    }
+
+    #[test]
+    fn repeat_greedy_first() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let string1 = find_syms!(&terms, Clone::clone, "p", "c", "a").unwrap();
+
+        let cons = Pattern::NonTerm(non_terms.find("C").unwrap().clone());
+        let star = Pattern::Star(Box::new(cons));
+        let empty = Pattern::Star(Box::new(Pattern::Start));
+
+        assert_eq!(
+            star.match_at_with(&string1, 0, MatchMode::First),
+            Match::new(vec![MatchSegment { start: 0, len: 2 }])
+        );
+        assert_eq!(
+            star.match_at(&string1, 2),
+            Match::new(vec![MatchSegment { start: 2, len: 0 }])
+        );
+        assert_eq!(
+            empty.match_terms(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 0 }])
+        );
+    }
+
+    #[test]
+    fn nested_repeat_long_input() {
+        let terms = terms_for_test();
+        let non_terms = non_terms_for_test(&terms).unwrap();
+        let vowels = find_syms!(&terms, Clone::clone, "a", "i", "u").unwrap();
+        let conss = find_syms!(&terms, Clone::clone, "p", "c", "q").unwrap();
+        let mut string1 = Vec::new();
+        for _ in 0 .. 10 {
+            string1.extend_from_slice(&vowels);
+        }
+        for _ in 0 .. 10 {
+            string1.extend_from_slice(&conss);
+        }
+
+        let vowel = Pattern::NonTerm(non_terms.find("V").unwrap().clone());
+        let cons = Pattern::NonTerm(non_terms.find("C").unwrap().clone());
+        let either = Pattern::Or(Box::new(vowel.clone()), Box::new(vowel));
+        let vowels = Pattern::Star(Box::new(Pattern::Star(Box::new(either))));
+        let conss = Pattern::And(
+            Box::new(Pattern::Star(Box::new(cons.clone()))),
+            Box::new(Pattern::Star(Box::new(cons))),
+        );
+        let pat = Pattern::And(Box::new(vowels), Box::new(conss));
+
+        let matches = pat.match_all_at(&string1, 0);
+        assert_eq!(matches.len(), 61);
+        assert_eq!(
+            pat.match_terms(&string1),
+            Match::new(vec![MatchSegment { start: 0, len: 60 }])
+        );
+        assert_eq!(
+            pat.find_at(&string1, 45),
+            Match::new(vec![MatchSegment { start: 45, len: 15 }])
         );
     }
 }
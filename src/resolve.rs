@@ -0,0 +1,1129 @@
+use crate::{
+    ast::{
+        BlockMode,
+        ClassDecl,
+        ClassExpr,
+        ClassExprKind,
+        Decl,
+        FeatureDecl,
+        Program,
+        RuleDecl,
+    },
+    engine::{Block, Mode},
+    error::{Diagnostic, ErrorKind},
+    feature::{FeatureDef, FeatureSystem, Matrix},
+    parser::Parser,
+    rule::Rule,
+    segment::segment,
+    source::{Span, Src},
+    symbol::{NonTerminal, Sequence, Symbol, Table, Terminal},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ptr,
+    rc::Rc,
+};
+
+#[derive(Debug, Clone)]
+pub struct Resolved {
+    pub terms: Table<Terminal>,
+    pub features: FeatureSystem,
+    pub non_terms: Table<NonTerminal>,
+    pub blocks: Vec<Block>,
+}
+
+#[allow(clippy::result_unit_err)]
+pub fn compile(src: &Src, errs: &mut Diagnostic) -> Result<Resolved, ()> {
+    let mut parser = Parser::new(src.reader(), errs);
+    let program = parser.parse_program(errs)?;
+    resolve(&program, errs)
+}
+
+#[allow(clippy::result_unit_err)]
+pub fn resolve(
+    program: &Program,
+    errs: &mut Diagnostic,
+) -> Result<Resolved, ()> {
+    let mut resolver = Resolver::new();
+    let terms = resolver.resolve_alphabet(program, errs);
+    let features = resolver.resolve_features(program, &terms, errs);
+    let non_terms = resolver.resolve_classes(program, &terms, &features, errs);
+    let blocks =
+        resolver.resolve_blocks(program, &terms, &features, &non_terms, errs);
+
+    if resolver.failed {
+        Err(())
+    } else {
+        Ok(Resolved { terms, features, non_terms, blocks })
+    }
+}
+
+#[derive(Debug)]
+struct Resolver {
+    failed: bool,
+}
+
+impl Resolver {
+    fn new() -> Self {
+        Self { failed: false }
+    }
+
+    fn raise(&mut self, kind: ErrorKind, errs: &mut Diagnostic) {
+        self.failed = true;
+        errs.raise(kind);
+    }
+
+    fn resolve_alphabet(
+        &mut self,
+        program: &Program,
+        errs: &mut Diagnostic,
+    ) -> Table<Terminal> {
+        let mut declared = HashMap::<&str, &Span>::new();
+        let mut terms = Vec::new();
+
+        for decl in &program.decls {
+            if let Decl::Alphabet(decl) = decl {
+                for symbol in &decl.symbols {
+                    if let Some(prev) = declared.get(&*symbol.content) {
+                        let err = ErrorKind::DuplicateSymbol(
+                            symbol.span.clone(),
+                            (*prev).clone(),
+                        );
+                        self.raise(err, errs);
+                    } else {
+                        if symbol.content == "0" {
+                            let span = symbol.span.clone();
+                            errs.warn(ErrorKind::NullSymbol(span));
+                        }
+                        declared.insert(&symbol.content, &symbol.span);
+                        terms.push(Terminal::new(&symbol.content));
+                    }
+                }
+            }
+        }
+
+        Table::new(terms)
+    }
+
+    // Features are resolved before classes, since classes may select segments
+    // by declared features. Feature values may therefore only list alphabet
+    // symbols and built-in feature matrices.
+    fn resolve_features(
+        &mut self,
+        program: &Program,
+        terms: &Table<Terminal>,
+        errs: &mut Diagnostic,
+    ) -> FeatureSystem {
+        let mut declared = HashMap::<&str, &Span>::new();
+        let mut defs = Vec::new();
+
+        for decl in &program.decls {
+            if let Decl::Feature(decl) = decl {
+                if let Some(prev) = declared.get(&*decl.name.content) {
+                    let err = ErrorKind::DuplicateFeature(
+                        decl.name.span.clone(),
+                        (*prev).clone(),
+                    );
+                    self.raise(err, errs);
+                } else {
+                    declared.insert(&decl.name.content, &decl.name.span);
+                    if let Some(def) = self.resolve_feature(decl, terms, errs) {
+                        defs.push(Rc::new(def));
+                    }
+                }
+            }
+        }
+
+        FeatureSystem::new(defs)
+    }
+
+    fn resolve_feature(
+        &mut self,
+        decl: &FeatureDecl,
+        terms: &Table<Terminal>,
+        errs: &mut Diagnostic,
+    ) -> Option<FeatureDef> {
+        let features = FeatureSystem::default();
+        let mut values = Vec::<(String, Vec<Terminal>)>::new();
+        let mut failed = false;
+
+        for value in &decl.values {
+            if let Some(class) = class_ref(&value.members) {
+                let err = ErrorKind::ClassInFeature(class.span.clone());
+                self.raise(err, errs);
+                failed = true;
+                continue;
+            }
+
+            let mut members = Vec::new();
+            let mut scope = ClassScope {
+                terms,
+                features: &features,
+                declared: &HashMap::new(),
+                defined: &HashMap::new(),
+                members: &mut members,
+            };
+            if self
+                .resolve_class_expr(&value.members, &mut scope, errs)
+                .is_err()
+            {
+                failed = true;
+                continue;
+            }
+
+            let desc = value.members.span.as_str();
+            let mut segments = Vec::new();
+            for leaf in NonTerminal::new(desc, members).leaves() {
+                match leaf {
+                    Symbol::Term(term) => segments.push(term),
+                    Symbol::Seq(seq) if seq.terms().len() == 1 => {
+                        segments.push(seq.terms()[0].clone())
+                    },
+                    _ => {
+                        let err =
+                            ErrorKind::NotASegment(value.members.span.clone());
+                        self.raise(err, errs);
+                        failed = true;
+                    },
+                }
+            }
+
+            for term in segments {
+                let conflict = values.iter().any(|(other, members)| {
+                    *other != value.value.content && members.contains(&term)
+                });
+                if conflict {
+                    let err = ErrorKind::ConflictingFeature(
+                        value.value.span.clone(),
+                        term.to_string(),
+                    );
+                    self.raise(err, errs);
+                    failed = true;
+                    continue;
+                }
+
+                match values
+                    .iter_mut()
+                    .find(|(other, _)| *other == value.value.content)
+                {
+                    Some((_, members)) if members.contains(&term) => (),
+                    Some((_, members)) => members.push(term),
+                    None => {
+                        values.push((value.value.content.clone(), vec![term]))
+                    },
+                }
+            }
+        }
+
+        if failed {
+            None
+        } else {
+            Some(FeatureDef::new(&*decl.name.content, values))
+        }
+    }
+
+    fn resolve_classes(
+        &mut self,
+        program: &Program,
+        terms: &Table<Terminal>,
+        features: &FeatureSystem,
+        errs: &mut Diagnostic,
+    ) -> Table<NonTerminal> {
+        let declared = self.collect_classes(program, errs);
+        let order = self.sort_classes(program, &declared, errs);
+        let mut defined = HashMap::new();
+
+        for decl in order {
+            let resolved = self.resolve_class(
+                decl, terms, features, &declared, &defined, errs,
+            );
+            if let Some(non_term) = resolved {
+                defined.insert(&*decl.name.content, non_term);
+            }
+        }
+
+        Table::new(defined.into_values())
+    }
+
+    fn collect_classes<'prog>(
+        &mut self,
+        program: &'prog Program,
+        errs: &mut Diagnostic,
+    ) -> HashMap<&'prog str, &'prog ClassDecl> {
+        let mut declared = HashMap::<&str, &ClassDecl>::new();
+
+        for decl in &program.decls {
+            if let Decl::Class(decl) = decl {
+                if let Some(prev) = declared.get(&*decl.name.content) {
+                    let err = ErrorKind::DuplicateClass(
+                        decl.name.span.clone(),
+                        prev.name.span.clone(),
+                    );
+                    self.raise(err, errs);
+                } else {
+                    declared.insert(&decl.name.content, decl);
+                }
+            }
+        }
+
+        declared
+    }
+
+    fn sort_classes<'prog>(
+        &mut self,
+        program: &'prog Program,
+        declared: &HashMap<&'prog str, &'prog ClassDecl>,
+        errs: &mut Diagnostic,
+    ) -> Vec<&'prog ClassDecl> {
+        let mut sorter = ClassSorter {
+            declared,
+            marks: HashMap::new(),
+            stack: Vec::new(),
+            order: Vec::new(),
+        };
+
+        for decl in &program.decls {
+            if let Decl::Class(decl) = decl {
+                let first = declared.get(&*decl.name.content);
+                if first.is_some_and(|first| ptr::eq(*first, decl)) {
+                    sorter.visit(decl, self, errs);
+                }
+            }
+        }
+
+        sorter.order
+    }
+
+    fn resolve_class(
+        &mut self,
+        decl: &ClassDecl,
+        terms: &Table<Terminal>,
+        features: &FeatureSystem,
+        declared: &HashMap<&str, &ClassDecl>,
+        defined: &HashMap<&str, NonTerminal>,
+        errs: &mut Diagnostic,
+    ) -> Option<NonTerminal> {
+        let mut members = Vec::new();
+        let mut scope = ClassScope {
+            terms,
+            features,
+            declared,
+            defined,
+            members: &mut members,
+        };
+        self.resolve_class_expr(&decl.expr, &mut scope, errs)
+            .ok()
+            .map(|()| NonTerminal::new(&*decl.name.content, members))
+    }
+
+    #[allow(clippy::unit_arg)]
+    fn resolve_class_expr(
+        &mut self,
+        expr: &ClassExpr,
+        scope: &mut ClassScope,
+        errs: &mut Diagnostic,
+    ) -> Result<(), ()> {
+        match &expr.kind {
+            ClassExprKind::Symbol(desc) => match segment(desc, scope.terms) {
+                Ok(mut word) if word.symbols.len() == 1 => {
+                    let term = word.symbols.remove(0);
+                    Ok(scope.members.push(Symbol::Term(term)))
+                },
+                Ok(word) => {
+                    let seq = Sequence::new(word.symbols);
+                    Ok(scope.members.push(Symbol::Seq(seq)))
+                },
+                Err(err) => {
+                    let err = ErrorKind::Unsegmentable(expr.span.clone(), err);
+                    Err(self.raise(err, errs))
+                },
+            },
+
+            ClassExprKind::Class(name) => match scope.defined.get(&**name) {
+                Some(non_term) => {
+                    Ok(scope.members.push(Symbol::NonTerm(non_term.clone())))
+                },
+                // Declared classes which are missing failed to resolve, and
+                // that has already been reported.
+                None if scope.declared.contains_key(&**name) => Err(()),
+                None => {
+                    let err = ErrorKind::UndefinedClass(expr.span.clone());
+                    Err(self.raise(err, errs))
+                },
+            },
+
+            ClassExprKind::Group(inner) => {
+                self.resolve_class_expr(inner, scope, errs)
+            },
+
+            ClassExprKind::Matrix(specs) => {
+                match Matrix::compile(specs, scope.features, errs) {
+                    Ok(matrix) => {
+                        Ok(scope.members.extend(matrix.members(scope.terms)))
+                    },
+                    Err(()) => Err(self.failed = true),
+                }
+            },
+
+            ClassExprKind::Not(inner) => {
+                let inner = self.resolve_operand(inner, scope, errs)?;
+                let desc = expr.span.as_str();
+                let complement = inner.complement(scope.terms, desc);
+                Ok(scope.members.extend(complement.leaves()))
+            },
+
+            ClassExprKind::Diff(left, right) => {
+                let left = self.resolve_operand(left, scope, errs);
+                let right = self.resolve_operand(right, scope, errs);
+                let desc = expr.span.as_str();
+                let difference = left?.difference(&right?, desc);
+                Ok(scope.members.extend(difference.leaves()))
+            },
+
+            ClassExprKind::Intersect(left, right) => {
+                let left = self.resolve_operand(left, scope, errs);
+                let right = self.resolve_operand(right, scope, errs);
+                let desc = expr.span.as_str();
+                let intersection = left?.intersection(&right?, desc);
+                Ok(scope.members.extend(intersection.leaves()))
+            },
+
+            ClassExprKind::Or(left, right) => {
+                let left = self.resolve_class_expr(left, scope, errs);
+                let right = self.resolve_class_expr(right, scope, errs);
+                left.and(right)
+            },
+        }
+    }
+
+    fn resolve_operand(
+        &mut self,
+        expr: &ClassExpr,
+        scope: &ClassScope,
+        errs: &mut Diagnostic,
+    ) -> Result<NonTerminal, ()> {
+        let mut members = Vec::new();
+        let mut operand = ClassScope {
+            terms: scope.terms,
+            features: scope.features,
+            declared: scope.declared,
+            defined: scope.defined,
+            members: &mut members,
+        };
+        self.resolve_class_expr(expr, &mut operand, errs)?;
+        Ok(NonTerminal::new(expr.span.as_str(), members))
+    }
+
+    // Consecutive top-level rules share one sequential block, so a program
+    // without explicit blocks behaves exactly as before.
+    fn resolve_blocks(
+        &mut self,
+        program: &Program,
+        terms: &Table<Terminal>,
+        features: &FeatureSystem,
+        non_terms: &Table<NonTerminal>,
+        errs: &mut Diagnostic,
+    ) -> Vec<Block> {
+        let declared = program
+            .decls
+            .iter()
+            .filter_map(|decl| match decl {
+                Decl::Class(decl) => Some(&*decl.name.content),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        let mut blocks = Vec::new();
+        let mut loose = Vec::new();
+
+        for decl in &program.decls {
+            match decl {
+                Decl::Rule(decl) => {
+                    if let Some(rule) = self.resolve_rule(
+                        decl, terms, features, non_terms, &declared, errs,
+                    ) {
+                        loose.push(rule);
+                    }
+                },
+
+                Decl::Block(decl) => {
+                    if !loose.is_empty() {
+                        let rules = std::mem::take(&mut loose);
+                        blocks.push(Block::new(Mode::Sequential, rules));
+                    }
+                    if decl.mode == BlockMode::Simultaneous {
+                        for rule in &decl.rules {
+                            for modifier in &rule.modifiers {
+                                let span = modifier.span.clone();
+                                errs.warn(ErrorKind::IgnoredModifier(span));
+                            }
+                        }
+                    }
+                    let rules = decl
+                        .rules
+                        .iter()
+                        .filter_map(|rule| {
+                            self.resolve_rule(
+                                rule, terms, features, non_terms, &declared,
+                                errs,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    let mode = match decl.mode {
+                        BlockMode::Sequential => Mode::Sequential,
+                        BlockMode::Simultaneous => Mode::Simultaneous,
+                        BlockMode::Iterative => Mode::Iterative,
+                    };
+                    blocks.push(Block::new(mode, rules));
+                },
+
+                _ => (),
+            }
+        }
+
+        if !loose.is_empty() {
+            blocks.push(Block::new(Mode::Sequential, loose));
+        }
+
+        blocks
+    }
+
+    fn resolve_rule(
+        &mut self,
+        decl: &RuleDecl,
+        terms: &Table<Terminal>,
+        features: &FeatureSystem,
+        non_terms: &Table<NonTerminal>,
+        declared: &HashSet<&str>,
+        errs: &mut Diagnostic,
+    ) -> Option<Rule> {
+        match Rule::compile(decl, terms, features, non_terms, declared, errs) {
+            Ok(rule) => Some(rule),
+            Err(()) => {
+                self.failed = true;
+                None
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClassScope<'scope, 'prog> {
+    terms: &'scope Table<Terminal>,
+    features: &'scope FeatureSystem,
+    declared: &'scope HashMap<&'prog str, &'prog ClassDecl>,
+    defined: &'scope HashMap<&'prog str, NonTerminal>,
+    members: &'scope mut Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+#[derive(Debug)]
+struct ClassSorter<'sorter, 'prog> {
+    declared: &'sorter HashMap<&'prog str, &'prog ClassDecl>,
+    marks: HashMap<&'prog str, Mark>,
+    stack: Vec<&'prog ClassDecl>,
+    order: Vec<&'prog ClassDecl>,
+}
+
+impl<'sorter, 'prog> ClassSorter<'sorter, 'prog> {
+    fn visit(
+        &mut self,
+        decl: &'prog ClassDecl,
+        resolver: &mut Resolver,
+        errs: &mut Diagnostic,
+    ) {
+        match self.marks.get(&*decl.name.content) {
+            Some(Mark::Done) => (),
+
+            Some(Mark::Visiting) => {
+                let start = self
+                    .stack
+                    .iter()
+                    .position(|other| other.name == decl.name)
+                    .expect("visiting class must be on the stack");
+                let cycle = self.stack[start ..]
+                    .iter()
+                    .map(|other| other.name.span.clone())
+                    .collect();
+                resolver.raise(ErrorKind::CyclicClass(cycle), errs);
+            },
+
+            None => {
+                self.marks.insert(&decl.name.content, Mark::Visiting);
+                self.stack.push(decl);
+
+                let mut deps = Vec::new();
+                class_deps(&decl.expr, &mut deps);
+                for dep in deps {
+                    if let Some(dep) = self.declared.get(dep) {
+                        self.visit(dep, resolver, errs);
+                    }
+                }
+
+                self.stack.pop();
+                self.marks.insert(&decl.name.content, Mark::Done);
+                self.order.push(decl);
+            },
+        }
+    }
+}
+
+fn class_deps<'prog>(expr: &'prog ClassExpr, deps: &mut Vec<&'prog str>) {
+    match &expr.kind {
+        ClassExprKind::Symbol(_) | ClassExprKind::Matrix(_) => (),
+        ClassExprKind::Class(name) => deps.push(name),
+        ClassExprKind::Group(inner) | ClassExprKind::Not(inner) => {
+            class_deps(inner, deps)
+        },
+        ClassExprKind::Diff(left, right)
+        | ClassExprKind::Intersect(left, right)
+        | ClassExprKind::Or(left, right) => {
+            class_deps(left, deps);
+            class_deps(right, deps);
+        },
+    }
+}
+
+fn class_ref(expr: &ClassExpr) -> Option<&ClassExpr> {
+    match &expr.kind {
+        ClassExprKind::Symbol(_) | ClassExprKind::Matrix(_) => None,
+        ClassExprKind::Class(_) => Some(expr),
+        ClassExprKind::Group(inner) | ClassExprKind::Not(inner) => {
+            class_ref(inner)
+        },
+        ClassExprKind::Diff(left, right)
+        | ClassExprKind::Intersect(left, right)
+        | ClassExprKind::Or(left, right) => {
+            class_ref(left).or_else(|| class_ref(right))
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compile, Resolved};
+    use crate::{
+        engine::{apply_rule, Mode},
+        error::{Diagnostic, ErrorKind},
+        find_syms,
+        pat::Pattern,
+        source::Src,
+        symbol::{DescKey, Symbol, Terminal, Word},
+    };
+
+    fn resolve_str(
+        source: &str,
+        errs: &mut Diagnostic,
+    ) -> Result<Resolved, ()> {
+        compile(&Src::new("foo.psh", source), errs)
+    }
+
+    #[test]
+    fn classes_and_rules() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, i, p, t, f
+            class \V = a | i
+            class \C = p | t
+            class \X = (\V | f)
+            rule p > f / \V _ \V
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(resolved.terms.as_slice().len(), 5);
+        assert_eq!(resolved.non_terms.as_slice().len(), 3);
+        assert_eq!(resolved.blocks.len(), 1);
+        assert_eq!(resolved.blocks[0].rules.len(), 1);
+
+        let vowel = resolved.non_terms.find("V").unwrap();
+        let other = resolved.non_terms.find("X").unwrap();
+        assert!(vowel.contains(resolved.terms.find("i").unwrap()));
+        assert!(!vowel.contains(resolved.terms.find("p").unwrap()));
+        assert!(other.contains(resolved.terms.find("a").unwrap()));
+        assert!(other.contains(resolved.terms.find("f").unwrap()));
+
+        match &other.members()[0] {
+            Symbol::NonTerm(non_term) => assert_eq!(non_term, vowel),
+            member => panic!("expected class, found {:?}", member.desc()),
+        }
+    }
+
+    #[test]
+    fn sequence_members() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, t, s
+            class \X = a | ts | 't'
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let other = resolved.non_terms.find("X").unwrap();
+        match &other.members()[1] {
+            Symbol::Seq(seq) => assert_eq!(seq.desc(), "ts"),
+            member => panic!("expected sequence, found {:?}", member.desc()),
+        }
+        match &other.members()[2] {
+            Symbol::Term(term) => assert_eq!(term.desc(), "t"),
+            member => panic!("expected terminal, found {:?}", member.desc()),
+        }
+    }
+
+    #[test]
+    fn negation_and_difference() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, i, p, b, t
+            class \V = a | i
+            class \C = !\V
+            class \X = \C - (p | b)
+            class \Y = !(\V | t) - b
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let descs = |name| {
+            let non_term = resolved.non_terms.find(name).unwrap();
+            non_term.members().iter().map(|sym| sym.desc()).collect::<Vec<_>>()
+        };
+        assert_eq!(descs("C"), ["b", "p", "t"]);
+        assert_eq!(descs("X"), ["t"]);
+        assert_eq!(descs("Y"), ["p"]);
+    }
+
+    #[test]
+    fn intersection() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet b, d, m, p, t
+            class \Voiced = b | d | m
+            class \Stop = p | t | b | d
+            class \X = \Voiced & \Stop
+            class \Y = \Stop - \Voiced | m
+            class \Z = \Stop & !\Voiced & t
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let find = |name| resolved.non_terms.find(name).unwrap();
+        let descs = |name| {
+            find(name)
+                .members()
+                .iter()
+                .map(|sym| sym.desc())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(descs("X"), ["b", "d"]);
+        assert_eq!(descs("Y"), ["p", "t", "m"]);
+        assert_eq!(descs("Z"), ["t"]);
+        assert!(find("X").is_subset(find("Stop")));
+        let swapped = find("Stop").intersection(find("Voiced"), "");
+        assert!(find("X").set_eq(&swapped));
+        assert!(!find("Y").set_eq(find("Stop")));
+    }
+
+    #[test]
+    fn undefined_names() {
+        let mut errs = Diagnostic::new();
+        let result = resolve_str(
+            r"
+            alphabet a, p
+            class \V = a | e
+            class \X = \C | p
+            class \Y = \Y | a
+            rule p > o / \V _
+            rule a > p / _ \Y
+            ",
+            &mut errs,
+        );
+
+        // Rules using the broken classes \V and \Y report nothing else.
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 4);
+        match &errs[0].kind {
+            ErrorKind::CyclicClass(spans) => assert_eq!(spans.len(), 1),
+            kind => panic!("expected cyclic class, found {:?}", kind),
+        }
+        match &errs[1].kind {
+            ErrorKind::Unsegmentable(span, _) => {
+                assert_eq!(span.as_str(), "e")
+            },
+            kind => panic!("expected unknown symbol, found {:?}", kind),
+        }
+        match &errs[2].kind {
+            ErrorKind::UndefinedClass(span) => {
+                assert_eq!(span.as_str(), r"\C")
+            },
+            kind => panic!("expected undefined class, found {:?}", kind),
+        }
+        match &errs[3].kind {
+            ErrorKind::Unsegmentable(span, _) => {
+                assert_eq!(span.as_str(), "o")
+            },
+            kind => panic!("expected unknown symbol, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn null_symbol() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet 0, a
+            rule '0' > a
+            rule 0 > a / _ 0
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::NullSymbol(span) if errs[0].warning => {
+                assert_eq!(span.as_str(), "0")
+            },
+            kind => panic!("expected null symbol, found {:?}", kind),
+        }
+
+        let rules = &resolved.blocks[0].rules;
+        assert!(!rules[0].is_insertion());
+        assert!(rules[1].is_insertion());
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut errs = Diagnostic::new();
+        let result = resolve_str(
+            r"
+            alphabet a, p
+            alphabet p
+            class \V = a
+            class \V = a
+            ",
+            &mut errs,
+        );
+
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 2);
+        match &errs[0].kind {
+            ErrorKind::DuplicateSymbol(span, prev) => {
+                assert_eq!(span.as_str(), "p");
+                assert_eq!(span.start().line(), 3);
+                assert_eq!(prev.start().line(), 2);
+            },
+            kind => panic!("expected duplicate symbol, found {:?}", kind),
+        }
+        match &errs[1].kind {
+            ErrorKind::DuplicateClass(span, prev) => {
+                assert_eq!(span.as_str(), r"\V");
+                assert_eq!(span.start().line(), 5);
+                assert_eq!(prev.start().line(), 4);
+            },
+            kind => panic!("expected duplicate class, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn forward_reference() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, i, p
+            class \X = \V | p
+            class \V = a | i
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let other = resolved.non_terms.find("X").unwrap();
+        assert!(other.contains(resolved.terms.find("i").unwrap()));
+    }
+
+    #[test]
+    fn self_cycle() {
+        let mut errs = Diagnostic::new();
+        let result = resolve_str(
+            r"
+            alphabet a, i
+            class \V = a | \V
+            ",
+            &mut errs,
+        );
+
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::CyclicClass(spans) => {
+                let names =
+                    spans.iter().map(|span| span.as_str()).collect::<Vec<_>>();
+                assert_eq!(names, [r"\V"]);
+            },
+            kind => panic!("expected cyclic class, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn transitive_cycle() {
+        let mut errs = Diagnostic::new();
+        let result = resolve_str(
+            r"
+            alphabet a, i, p
+            class \A = \B | a
+            class \B = i | (\C)
+            class \C = \A
+            class \D = \B | p
+            ",
+            &mut errs,
+        );
+
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::CyclicClass(spans) => {
+                let names =
+                    spans.iter().map(|span| span.as_str()).collect::<Vec<_>>();
+                assert_eq!(names, [r"\A", r"\B", r"\C"]);
+                assert_eq!(spans[0].start().line(), 3);
+                assert_eq!(spans[2].start().line(), 5);
+            },
+            kind => panic!("expected cyclic class, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn rule_blocks() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, e, i, t
+            rule a > e
+            rule e > i
+            simultaneous { rule a > e  rule e > i }
+            rule t > a
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let modes = resolved
+            .blocks
+            .iter()
+            .map(|block| (block.mode, block.rules.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            modes,
+            [
+                (Mode::Sequential, 2),
+                (Mode::Simultaneous, 2),
+                (Mode::Sequential, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn simultaneous_modifiers() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, e, i
+            simultaneous { rule rtl a > e  rule feeding e > i }
+            iterative { rule rtl a > e }
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(resolved.blocks.len(), 2);
+        let errs = errs.take_errors();
+        let spans = errs
+            .iter()
+            .map(|err| match &err.kind {
+                ErrorKind::IgnoredModifier(span) if err.warning => {
+                    span.as_str()
+                },
+                kind => panic!("expected ignored modifier, found {:?}", kind),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(spans, ["rtl", "feeding"]);
+    }
+
+    #[test]
+    fn feature_matrix() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet a, b, d, p, t, s
+            class \P = [+stop -voice]
+            class \S = [-syllabic] - \P
+            rule [+stop +voice] > p / _ #
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let descs = |name| {
+            let class = resolved.non_terms.find(name).unwrap();
+            class
+                .leaves()
+                .iter()
+                .map(|leaf| leaf.desc().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(descs("P"), ["p", "t"]);
+        assert_eq!(descs("S"), ["b", "d", "s"]);
+        assert!(matches!(
+            resolved.blocks[0].rules[0].target,
+            Pattern::Matrix(_)
+        ));
+    }
+
+    #[test]
+    fn unknown_feature() {
+        let mut errs = Diagnostic::new();
+        let result = resolve_str(
+            r"
+            alphabet b, p
+            class \P = [+stop -vocie]
+            rule [+stp] > p
+            ",
+            &mut errs,
+        );
+
+        assert!(result.is_err());
+        let names = errs
+            .as_slice()
+            .iter()
+            .map(|err| match &err.kind {
+                ErrorKind::UnknownFeature(span) => span.as_str(),
+                kind => panic!("expected unknown feature, found {:?}", kind),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["vocie", "stp"]);
+    }
+
+    #[test]
+    fn declared_features() {
+        let mut errs = Diagnostic::new();
+        let resolved = resolve_str(
+            r"
+            alphabet P, B, T, D, a
+            feature voice = +(B | D) -(P | T)
+            feature place = labial (P | B) coronal T coronal D
+            class \V = [+voice]
+            rule [-voice +coronal] > [+voice]
+            ",
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let defs = resolved
+            .features
+            .defs()
+            .iter()
+            .map(|def| {
+                let values = def
+                    .values()
+                    .iter()
+                    .map(|(value, members)| {
+                        let members = members
+                            .iter()
+                            .map(|term| term.desc())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        format!("{} {}", value, members)
+                    })
+                    .collect::<Vec<_>>();
+                (def.name(), values)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            defs,
+            [
+                ("voice", vec!["+ B D".to_owned(), "- P T".to_owned()]),
+                (
+                    "place",
+                    vec!["labial P B".to_owned(), "coronal T D".to_owned()]
+                ),
+            ]
+        );
+
+        let leaves = resolved.non_terms.find("V").unwrap().leaves();
+        let descs = leaves.iter().map(|leaf| leaf.desc()).collect::<Vec<_>>();
+        assert_eq!(descs, ["B", "D", "a"]);
+
+        let word = Word {
+            symbols: find_syms!(&resolved.terms, Terminal::clone, "P", "T")
+                .unwrap(),
+        };
+        let rule = &resolved.blocks[0].rules[0];
+        assert_eq!(apply_rule(rule, &word).to_string(), "PD");
+    }
+
+    #[test]
+    fn bad_feature_decls() {
+        let mut errs = Diagnostic::new();
+        let result = resolve_str(
+            r"
+            alphabet p, t, k
+            feature voice = +p -(p | t)
+            feature voice = +k
+            feature tense = +pt
+            class \P = p | t
+            feature labial = +p -(\P - p)
+            ",
+            &mut errs,
+        );
+
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 4);
+        match &errs[0].kind {
+            ErrorKind::ConflictingFeature(span, desc) => {
+                assert_eq!(span.as_str(), "-");
+                assert_eq!(desc, "p");
+            },
+            kind => panic!("expected conflicting feature, found {:?}", kind),
+        }
+        match &errs[1].kind {
+            ErrorKind::DuplicateFeature(span, _) => {
+                assert_eq!(span.as_str(), "voice")
+            },
+            kind => panic!("expected duplicate feature, found {:?}", kind),
+        }
+        match &errs[2].kind {
+            ErrorKind::NotASegment(span) => assert_eq!(span.as_str(), "pt"),
+            kind => panic!("expected non-segment, found {:?}", kind),
+        }
+        match &errs[3].kind {
+            ErrorKind::ClassInFeature(span) => assert_eq!(span.as_str(), r"\P"),
+            kind => panic!("expected class in feature, found {:?}", kind),
+        }
+    }
+}
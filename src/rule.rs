@@ -0,0 +1,966 @@
+use crate::{
+    ast::{
+        PatternExpr,
+        PatternExprKind,
+        ReplacementExpr,
+        ReplacementExprKind,
+        RuleDecl,
+        RuleModifierKind,
+    },
+    engine::Direction,
+    error::{Diagnostic, ErrorKind},
+    feature::{FeatureSystem, Matrix},
+    pat::{CaptureName, Match, Pattern},
+    segment::segment,
+    source::Span,
+    symbol::{DescKey, NonTerminal, Sequence, Symbol, Table, Terminal},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Replacement {
+    Term(Terminal),
+    Class(NonTerminal),
+    Capture(CaptureName),
+    Matrix(Matrix, HashMap<Terminal, Terminal>),
+}
+
+impl fmt::Display for Replacement {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Replacement::Term(term) => fmt.write_str(term.desc()),
+            Replacement::Class(class) => fmt.write_str(class.desc()),
+            Replacement::Capture(name) => write!(fmt, "{}", name),
+            Replacement::Matrix(matrix, _) => write!(fmt, "{}", matrix),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub direction: Direction,
+    pub feeding: bool,
+    pub target: Pattern,
+    pub replacement: Vec<Replacement>,
+    pub left: Option<Pattern>,
+    pub right: Option<Pattern>,
+}
+
+impl Rule {
+    #[allow(clippy::result_unit_err)]
+    pub fn compile(
+        decl: &RuleDecl,
+        terms: &Table<Terminal>,
+        features: &FeatureSystem,
+        non_terms: &Table<NonTerminal>,
+        declared: &HashSet<&str>,
+        errs: &mut Diagnostic,
+    ) -> Result<Self, ()> {
+        let compiler = Compiler { terms, features, non_terms, declared };
+
+        // A class in the replacement maps members positionally, so the target
+        // has to be a class as well.
+        let corresponds = decl
+            .replacement
+            .iter()
+            .any(|item| matches!(item.kind, ReplacementExprKind::Class(_)));
+        let target = if corresponds {
+            compiler.class(&decl.target, errs).map(Pattern::NonTerm)
+        } else {
+            compiler.pattern(&decl.target, Side::Target, errs)
+        };
+
+        let target_class = match &target {
+            Ok(Pattern::NonTerm(class)) => Some((class, &decl.target.span)),
+            _ => None,
+        };
+        let mut captures = Vec::new();
+        capture_names(&decl.target, false, &mut captures);
+        if let Some(env) = &decl.env {
+            for side in env.left.iter().chain(&env.right) {
+                capture_names(side, false, &mut captures);
+            }
+        }
+
+        // A capture repeated by the rule would refer back to its first
+        // match on every repetition after it.
+        let mut repeated = Ok(());
+        for (_, capture, in_repetition) in &captures {
+            if *in_repetition {
+                let err = ErrorKind::RepeatedCapture(capture.span.clone());
+                errs.raise(err);
+                repeated = Err(());
+            }
+        }
+
+        let names =
+            captures.iter().map(|(name, _, _)| *name).collect::<Vec<_>>();
+        let replacement = compiler.replacement(
+            &decl.replacement,
+            target.as_ref().ok(),
+            target_class,
+            &names,
+            errs,
+        );
+
+        let (left, right) = match &decl.env {
+            Some(env) => (
+                compiler.opt_pattern(env.left.as_ref(), Side::Left, errs),
+                compiler.opt_pattern(env.right.as_ref(), Side::Right, errs),
+            ),
+            None => (Ok(None), Ok(None)),
+        };
+
+        let mut direction = Direction::Ltr;
+        let mut feeding = false;
+        for modifier in &decl.modifiers {
+            match modifier.kind {
+                RuleModifierKind::Ltr => direction = Direction::Ltr,
+                RuleModifierKind::Rtl => direction = Direction::Rtl,
+                RuleModifierKind::Feeding => feeding = true,
+            }
+        }
+
+        repeated?;
+        Ok(Self {
+            direction,
+            feeding,
+            target: target?,
+            replacement: replacement?,
+            left: left?,
+            right: right?,
+        })
+    }
+
+    pub fn replace(&self, input: &[Terminal], found: &Match) -> Vec<Terminal> {
+        let mut replaced = Vec::new();
+        let matched = &input[found.general_start() .. found.general_end()];
+        let index = self.member_index(matched);
+
+        for (i, item) in self.replacement.iter().enumerate() {
+            match item {
+                Replacement::Term(term) => replaced.push(term.clone()),
+                Replacement::Class(class) => {
+                    let leaves = class.leaves();
+                    if let Some(leaf) = index.and_then(|i| leaves.get(i)) {
+                        replaced.extend_from_slice(leaf_terms(leaf));
+                    }
+                },
+                Replacement::Capture(name) => {
+                    if let Some(capture) = found.capture(name) {
+                        replaced.extend_from_slice(&capture.terms);
+                    }
+                },
+                // A matrix changes the matched segment in its own position.
+                Replacement::Matrix(_, changes) => {
+                    if let Some(term) = matched.get(i) {
+                        replaced
+                            .push(changes.get(term).unwrap_or(term).clone());
+                    }
+                },
+            }
+        }
+
+        replaced
+    }
+
+    pub fn is_insertion(&self) -> bool {
+        matches!(&self.target, Pattern::Terms(terms) if terms.is_empty())
+    }
+
+    fn member_index(&self, matched: &[Terminal]) -> Option<usize> {
+        match &self.target {
+            Pattern::NonTerm(class) => class
+                .leaves()
+                .iter()
+                .position(|leaf| leaf_terms(leaf) == matched),
+            _ => None,
+        }
+    }
+}
+
+fn leaf_terms(leaf: &Symbol) -> &[Terminal] {
+    match leaf {
+        Symbol::Term(term) => std::slice::from_ref(term),
+        Symbol::Seq(seq) => seq.terms(),
+        Symbol::NonTerm(_) => &[],
+    }
+}
+
+// Splits a target into the patterns for each of its positions, as long as
+// every one of them matches exactly one segment.
+fn single_segments(pat: &Pattern) -> Option<Vec<Pattern>> {
+    match pat {
+        Pattern::Terms(terms) => Some(
+            terms
+                .iter()
+                .map(|term| Pattern::Terms(vec![term.clone()]))
+                .collect(),
+        ),
+        Pattern::Matrix(_) => Some(vec![pat.clone()]),
+        Pattern::NonTerm(class)
+            if class
+                .leaves()
+                .iter()
+                .all(|leaf| matches!(leaf, Symbol::Term(_))) =>
+        {
+            Some(vec![pat.clone()])
+        },
+        Pattern::Capture(_, inner) => single_segments(inner),
+        Pattern::And(left, right) => {
+            let mut positions = single_segments(left)?;
+            positions.append(&mut single_segments(right)?);
+            Some(positions)
+        },
+        _ => None,
+    }
+}
+
+// Collects every capture along with whether it is inside a repetition.
+fn capture_names<'expr>(
+    expr: &'expr PatternExpr,
+    repeated: bool,
+    names: &mut Vec<(&'expr CaptureName, &'expr PatternExpr, bool)>,
+) {
+    match &expr.kind {
+        PatternExprKind::Capture(inner, name) => {
+            names.push((name, expr, repeated));
+            capture_names(inner, repeated, names);
+        },
+
+        PatternExprKind::Group(inner) | PatternExprKind::Optional(inner) => {
+            capture_names(inner, repeated, names)
+        },
+
+        PatternExprKind::Star(inner) | PatternExprKind::Plus(inner) => {
+            capture_names(inner, true, names)
+        },
+
+        PatternExprKind::Repeat(inner, _, max) => {
+            let many = max.is_none_or(|max| max > 1);
+            capture_names(inner, repeated || many, names)
+        },
+
+        PatternExprKind::And(left, right)
+        | PatternExprKind::Or(left, right) => {
+            capture_names(left, repeated, names);
+            capture_names(right, repeated, names);
+        },
+
+        _ => (),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Target,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Compiler<'tables> {
+    terms: &'tables Table<Terminal>,
+    features: &'tables FeatureSystem,
+    non_terms: &'tables Table<NonTerminal>,
+    declared: &'tables HashSet<&'tables str>,
+}
+
+impl<'tables> Compiler<'tables> {
+    fn replacement(
+        &self,
+        items: &[ReplacementExpr],
+        pattern: Option<&Pattern>,
+        target: Option<(&NonTerminal, &Span)>,
+        names: &[&CaptureName],
+        errs: &mut Diagnostic,
+    ) -> Result<Vec<Replacement>, ()> {
+        let mut compiled = Vec::with_capacity(items.len());
+        let mut failed = false;
+        let positions = pattern.and_then(single_segments);
+
+        for item in items {
+            match &item.kind {
+                ReplacementExprKind::Null => (),
+
+                ReplacementExprKind::Symbol(desc) => {
+                    match self.segment(desc, &item.span, errs) {
+                        Ok(word) => compiled
+                            .extend(word.into_iter().map(Replacement::Term)),
+                        Err(()) => failed = true,
+                    }
+                },
+
+                ReplacementExprKind::Class(expr) => {
+                    match self.replacement_class(expr, target, errs) {
+                        Ok(class) => compiled.push(Replacement::Class(class)),
+                        Err(()) => failed = true,
+                    }
+                },
+
+                // A reference must name a captured element of the target or
+                // environment, as in `\C₁ \L₂ > \L₂ \C₁`.
+                ReplacementExprKind::Ref(_, name) => {
+                    if names.contains(&name) {
+                        compiled.push(Replacement::Capture(name.clone()));
+                    } else {
+                        let err =
+                            ErrorKind::UndefinedCapture(item.span.clone());
+                        errs.raise(err);
+                        failed = true;
+                    }
+                },
+
+                // Only a target whose every position is a single segment
+                // lines up with the matrices of the replacement.
+                ReplacementExprKind::Matrix(specs) => {
+                    let position = positions
+                        .as_ref()
+                        .and_then(|positions| positions.get(compiled.len()));
+                    match Matrix::compile(specs, self.features, errs) {
+                        Ok(matrix)
+                            if position.is_some() || pattern.is_none() =>
+                        {
+                            compiled.push(self.feature_change(
+                                matrix, position, &item.span, errs,
+                            ))
+                        },
+                        Ok(_) => {
+                            let err =
+                                ErrorKind::UnalignedMatrix(item.span.clone());
+                            errs.raise(err);
+                            failed = true;
+                        },
+                        Err(()) => failed = true,
+                    }
+                },
+            }
+        }
+
+        if failed {
+            Err(())
+        } else {
+            Ok(compiled)
+        }
+    }
+
+    // Changes are looked up once for every segment of the alphabet. Segments
+    // the target can match in the matrix's position but which have no changed
+    // counterpart are only warned about, and the rule leaves them as they are.
+    fn feature_change(
+        &self,
+        matrix: Matrix,
+        target: Option<&Pattern>,
+        span: &Span,
+        errs: &mut Diagnostic,
+    ) -> Replacement {
+        let mut changes = HashMap::new();
+
+        for term in self.terms.as_slice() {
+            match matrix.change(term, self.terms, self.features) {
+                Some(changed) => {
+                    changes.insert(term.clone(), changed.clone());
+                },
+                None if target.is_some_and(|target| {
+                    target
+                        .match_all_at(std::slice::from_ref(term), 0)
+                        .iter()
+                        .any(|found| found.general_end() == 1)
+                }) =>
+                {
+                    let err =
+                        ErrorKind::NoSegment(span.clone(), term.to_string());
+                    errs.warn(err);
+                },
+                None => (),
+            }
+        }
+
+        Replacement::Matrix(matrix, changes)
+    }
+
+    #[allow(clippy::unit_arg)]
+    fn replacement_class(
+        &self,
+        expr: &PatternExpr,
+        target: Option<(&NonTerminal, &Span)>,
+        errs: &mut Diagnostic,
+    ) -> Result<NonTerminal, ()> {
+        let class = self.class(expr, errs)?;
+
+        if let Some((target, span)) = target {
+            let arity = class.leaves().len();
+            let expected = target.leaves().len();
+            if arity != expected {
+                let err = ErrorKind::ArityMismatch(
+                    expr.span.clone(),
+                    arity,
+                    span.clone(),
+                    expected,
+                );
+                return Err(errs.raise(err));
+            }
+        }
+
+        Ok(class)
+    }
+
+    #[allow(clippy::unit_arg)]
+    fn segment(
+        &self,
+        desc: &str,
+        span: &Span,
+        errs: &mut Diagnostic,
+    ) -> Result<Vec<Terminal>, ()> {
+        match segment(desc, self.terms) {
+            Ok(word) => Ok(word.symbols),
+            Err(err) => {
+                Err(errs.raise(ErrorKind::Unsegmentable(span.clone(), err)))
+            },
+        }
+    }
+
+    // Only a name that was never declared is undefined. A declared class
+    // missing from the table still fails the rule, but raises nothing new.
+    #[allow(clippy::unit_arg)]
+    fn non_term(
+        &self,
+        name: &str,
+        span: &Span,
+        errs: &mut Diagnostic,
+    ) -> Result<NonTerminal, ()> {
+        match self.non_terms.find(name) {
+            Some(non_term) => Ok(non_term.clone()),
+            None if self.declared.contains(name) => Err(()),
+            None => Err(errs.raise(ErrorKind::UndefinedClass(span.clone()))),
+        }
+    }
+
+    fn opt_pattern(
+        &self,
+        expr: Option<&PatternExpr>,
+        side: Side,
+        errs: &mut Diagnostic,
+    ) -> Result<Option<Pattern>, ()> {
+        expr.map(|expr| self.pattern(expr, side, errs)).transpose()
+    }
+
+    #[allow(clippy::unit_arg)]
+    fn pattern(
+        &self,
+        expr: &PatternExpr,
+        side: Side,
+        errs: &mut Diagnostic,
+    ) -> Result<Pattern, ()> {
+        match &expr.kind {
+            PatternExprKind::Null => Ok(Pattern::Terms(Vec::new())),
+
+            PatternExprKind::Symbol(desc) => {
+                self.segment(desc, &expr.span, errs).map(Pattern::Terms)
+            },
+
+            PatternExprKind::Class(name) => {
+                self.non_term(name, &expr.span, errs).map(Pattern::NonTerm)
+            },
+
+            PatternExprKind::Boundary => match side {
+                Side::Left => Ok(Pattern::Start),
+                Side::Right => Ok(Pattern::End),
+                Side::Target => {
+                    let err = ErrorKind::BoundaryInTarget(expr.span.clone());
+                    Err(errs.raise(err))
+                },
+            },
+
+            PatternExprKind::Group(inner) => self.pattern(inner, side, errs),
+
+            PatternExprKind::Matrix(specs) => {
+                Matrix::compile(specs, self.features, errs).map(Pattern::Matrix)
+            },
+
+            PatternExprKind::Optional(inner) => {
+                let inner = self.pattern(inner, side, errs)?;
+                Ok(Pattern::Optional(Box::new(inner)))
+            },
+
+            PatternExprKind::Star(inner) => {
+                let inner = self.pattern(inner, side, errs)?;
+                Ok(Pattern::Star(Box::new(inner)))
+            },
+
+            PatternExprKind::Plus(inner) => {
+                let inner = self.pattern(inner, side, errs)?;
+                Ok(Pattern::Plus(Box::new(inner)))
+            },
+
+            PatternExprKind::Repeat(inner, min, max) => {
+                let inner = self.pattern(inner, side, errs)?;
+                Ok(Pattern::Repeat(Box::new(inner), *min, *max))
+            },
+
+            PatternExprKind::Capture(inner, name) => {
+                let inner = self.pattern(inner, side, errs)?;
+                Ok(Pattern::Capture(name.clone(), Box::new(inner)))
+            },
+
+            PatternExprKind::Not(_)
+            | PatternExprKind::Diff(_, _)
+            | PatternExprKind::Intersect(_, _) => {
+                self.class(expr, errs).map(Pattern::NonTerm)
+            },
+
+            PatternExprKind::And(left, right) => {
+                let left = self.pattern(left, side, errs);
+                let right = self.pattern(right, side, errs);
+                Ok(Pattern::And(Box::new(left?), Box::new(right?)))
+            },
+
+            PatternExprKind::Or(left, right) => {
+                let left = self.pattern(left, side, errs);
+                let right = self.pattern(right, side, errs);
+                Ok(Pattern::Or(Box::new(left?), Box::new(right?)))
+            },
+        }
+    }
+
+    fn class(
+        &self,
+        expr: &PatternExpr,
+        errs: &mut Diagnostic,
+    ) -> Result<NonTerminal, ()> {
+        let mut members = Vec::new();
+        self.class_members(expr, &mut members, errs)?;
+        Ok(NonTerminal::new(expr.span.as_str(), members))
+    }
+
+    #[allow(clippy::unit_arg)]
+    fn class_members(
+        &self,
+        expr: &PatternExpr,
+        members: &mut Vec<Symbol>,
+        errs: &mut Diagnostic,
+    ) -> Result<(), ()> {
+        match &expr.kind {
+            PatternExprKind::Symbol(desc) => {
+                let mut terms = self.segment(desc, &expr.span, errs)?;
+                if terms.len() == 1 {
+                    Ok(members.push(Symbol::Term(terms.remove(0))))
+                } else {
+                    Ok(members.push(Symbol::Seq(Sequence::new(terms))))
+                }
+            },
+
+            PatternExprKind::Class(name) => {
+                let non_term = self.non_term(name, &expr.span, errs)?;
+                Ok(members.push(Symbol::NonTerm(non_term)))
+            },
+
+            PatternExprKind::Group(inner) => {
+                self.class_members(inner, members, errs)
+            },
+
+            PatternExprKind::Matrix(specs) => {
+                let matrix = Matrix::compile(specs, self.features, errs)?;
+                Ok(members.extend(matrix.members(self.terms)))
+            },
+
+            PatternExprKind::Not(inner) => {
+                let inner = self.class(inner, errs)?;
+                let desc = expr.span.as_str();
+                let complement = inner.complement(self.terms, desc);
+                Ok(members.extend(complement.leaves()))
+            },
+
+            PatternExprKind::Diff(left, right) => {
+                let left = self.class(left, errs);
+                let right = self.class(right, errs);
+                let desc = expr.span.as_str();
+                let difference = left?.difference(&right?, desc);
+                Ok(members.extend(difference.leaves()))
+            },
+
+            PatternExprKind::Intersect(left, right) => {
+                let left = self.class(left, errs);
+                let right = self.class(right, errs);
+                let desc = expr.span.as_str();
+                let intersection = left?.intersection(&right?, desc);
+                Ok(members.extend(intersection.leaves()))
+            },
+
+            PatternExprKind::Or(left, right) => {
+                let left = self.class_members(left, members, errs);
+                let right = self.class_members(right, members, errs);
+                left.and(right)
+            },
+
+            _ => Err(errs.raise(ErrorKind::NotAClass(expr.span.clone()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rule;
+    use crate::{
+        ast::Decl,
+        error::{Diagnostic, ErrorKind},
+        feature::FeatureSystem,
+        find_syms,
+        make_terms,
+        parser::Parser,
+        pat::Pattern,
+        source::Src,
+        symbol::{DescKey, NonTerminal, Symbol, Table, Terminal},
+    };
+    use std::collections::HashSet;
+
+    fn tables_for_test() -> (Table<Terminal>, Table<NonTerminal>) {
+        fn wrap(term: &Terminal) -> Symbol {
+            Symbol::Term(term.clone())
+        }
+
+        let terms = make_terms!("a", "i", "p", "f");
+        let vowels = find_syms!(&terms, wrap, "a", "i").unwrap();
+        let non_terms = Table::new(vec![NonTerminal::new("V", vowels)]);
+        (terms, non_terms)
+    }
+
+    fn compile_first(source: &str, errs: &mut Diagnostic) -> Result<Rule, ()> {
+        let (terms, non_terms) = tables_for_test();
+        compile_with(source, &terms, &non_terms, errs)
+    }
+
+    fn compile_with(
+        source: &str,
+        terms: &Table<Terminal>,
+        non_terms: &Table<NonTerminal>,
+        errs: &mut Diagnostic,
+    ) -> Result<Rule, ()> {
+        let src = Src::new("foo.psh", source);
+        let mut parser = Parser::new(src.reader(), errs);
+        let program = parser.parse_program(errs)?;
+        match &program.decls[0] {
+            Decl::Rule(decl) => {
+                let features = FeatureSystem::default();
+                let declared = HashSet::new();
+                Rule::compile(
+                    decl, terms, &features, non_terms, &declared, errs,
+                )
+            },
+            decl => panic!("expected rule, found {:?}", decl),
+        }
+    }
+
+    fn replace(rule: &Rule, terms: &Table<Terminal>, descs: &[&str]) -> String {
+        let input = descs
+            .iter()
+            .map(|desc| terms.find(desc).unwrap().clone())
+            .collect::<Vec<_>>();
+        let found = rule.target.find(&input);
+        rule.replace(&input, &found).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn intervocalic() {
+        let mut errs = Diagnostic::new();
+        let rule = compile_first(r"rule p > f / \V _ \V", &mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        match &rule.target {
+            Pattern::Terms(terms) => {
+                assert_eq!(terms.len(), 1);
+                assert_eq!(terms[0].to_string(), "p");
+            },
+            pat => panic!("expected terminals, found {:?}", pat),
+        }
+        assert_eq!(rule.replacement.len(), 1);
+        assert_eq!(rule.replacement[0].to_string(), "f");
+        match (&rule.left, &rule.right) {
+            (Some(Pattern::NonTerm(left)), Some(Pattern::NonTerm(right))) => {
+                assert_eq!(left.to_string(), "V");
+                assert_eq!(right.to_string(), "V");
+            },
+            env => panic!("expected classes, found {:?}", env),
+        }
+    }
+
+    #[test]
+    fn multigraph_strings() {
+        let mut errs = Diagnostic::new();
+        let rule = compile_first("rule pa > 'fi' a", &mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        match &rule.target {
+            Pattern::Terms(terms) => {
+                let descs =
+                    terms.iter().map(ToString::to_string).collect::<Vec<_>>();
+                assert_eq!(descs, ["p", "a"]);
+            },
+            pat => panic!("expected terminals, found {:?}", pat),
+        }
+        let descs = rule
+            .replacement
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(descs, ["f", "i", "a"]);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        let mut errs = Diagnostic::new();
+        let rule = compile_first("rule p > f / # _ #", &mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(rule.left, Some(Pattern::Start));
+        assert_eq!(rule.right, Some(Pattern::End));
+
+        let result = compile_first("rule p # > f", &mut errs);
+        assert!(result.is_err());
+        assert_eq!(errs.as_slice().len(), 1);
+    }
+
+    #[test]
+    fn inline_negation() {
+        let mut errs = Diagnostic::new();
+        let rule =
+            compile_first(r"rule p > f / !\V _ \V - i", &mut errs).unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        match (&rule.left, &rule.right) {
+            (Some(Pattern::NonTerm(left)), Some(Pattern::NonTerm(right))) => {
+                let descs = |non_term: &NonTerminal| {
+                    non_term
+                        .members()
+                        .iter()
+                        .map(|sym| sym.desc().to_owned())
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(left.to_string(), r"!\V");
+                assert_eq!(descs(left), ["f", "p"]);
+                assert_eq!(descs(right), ["a"]);
+            },
+            env => panic!("expected classes, found {:?}", env),
+        }
+
+        let result = compile_first(r"rule p > f / !(\V a) _", &mut errs);
+        assert!(result.is_err());
+        assert_eq!(errs.as_slice().len(), 1);
+    }
+
+    #[test]
+    fn class_correspondence() {
+        let (terms, non_terms) = tables_for_test();
+        let compile = |source, errs: &mut Diagnostic| {
+            compile_with(source, &terms, &non_terms, errs).unwrap()
+        };
+
+        let mut errs = Diagnostic::new();
+        let rule = compile(r"rule (p | a) > (f | i)", &mut errs);
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert!(matches!(rule.target, Pattern::NonTerm(_)));
+        assert_eq!(rule.replacement.len(), 1);
+        assert_eq!(rule.replacement[0].to_string(), "(f | i)");
+        assert_eq!(replace(&rule, &terms, &["p"]), "f");
+        assert_eq!(replace(&rule, &terms, &["a"]), "i");
+
+        let rule = compile(r"rule \V > a (p | f)", &mut errs);
+        assert_eq!(replace(&rule, &terms, &["i"]), "af");
+    }
+
+    #[test]
+    fn metathesis() {
+        let (terms, non_terms) = tables_for_test();
+        let mut errs = Diagnostic::new();
+        let rule = compile_with(
+            r"rule p₁ \V₂ f? > \V₂ p₁",
+            &terms,
+            &non_terms,
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        let descs = rule
+            .replacement
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(descs, ["₂", "₁"]);
+        assert_eq!(replace(&rule, &terms, &["p", "a"]), "ap");
+        assert_eq!(replace(&rule, &terms, &["p", "i", "f"]), "ip");
+
+        let result = compile_first(r"rule p₁ \V₂ > \V₂ p₃", &mut errs);
+        assert!(result.is_err());
+        assert_eq!(errs.as_slice().len(), 1);
+    }
+
+    #[test]
+    fn capture_references() {
+        let (terms, non_terms) = tables_for_test();
+        let mut errs = Diagnostic::new();
+        let rule = compile_with(
+            r"rule (p|f)₁ a > a (p | f)₁",
+            &terms,
+            &non_terms,
+            &mut errs,
+        )
+        .unwrap();
+
+        // References are resolved by their name only.
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(replace(&rule, &terms, &["f", "a"]), "af");
+
+        let result = compile_with(
+            r"rule (\V₁)+ > ∅ / _ (p@c){2}",
+            &terms,
+            &non_terms,
+            &mut errs,
+        );
+        assert!(result.is_err());
+        let spans = errs
+            .take_errors()
+            .into_iter()
+            .map(|err| match err.kind {
+                ErrorKind::RepeatedCapture(span) => span.as_str().to_owned(),
+                err => panic!("expected repeated capture, found {:?}", err),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(spans, [r"\V₁", "p@c"]);
+    }
+
+    #[test]
+    fn named_captures() {
+        let (terms, non_terms) = tables_for_test();
+        let mut errs = Diagnostic::new();
+        let rule = compile_with(
+            r"rule ∅ > \V@v / \V@v p₁ _ p₁",
+            &terms,
+            &non_terms,
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(errs.as_slice().len(), 0);
+        assert_eq!(rule.replacement[0].to_string(), "@v");
+
+        let result = compile_first(r"rule a > \V@w / \V@v _", &mut errs);
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::UndefinedCapture(span) => {
+                assert_eq!(span.as_str(), r"\V@w")
+            },
+            err => panic!("expected undefined capture, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn feature_change() {
+        let terms = make_terms!("a", "p", "t", "k", "ɸ", "s");
+        let non_terms = Table::new(Vec::new());
+        let mut errs = Diagnostic::new();
+        let rule = compile_with(
+            "rule [+stop] > [+fricative] / a _ a",
+            &terms,
+            &non_terms,
+            &mut errs,
+        )
+        .unwrap();
+
+        assert_eq!(rule.replacement[0].to_string(), "[+fricative]");
+        assert_eq!(replace(&rule, &terms, &["p"]), "ɸ");
+        assert_eq!(replace(&rule, &terms, &["t"]), "s");
+        assert_eq!(replace(&rule, &terms, &["k"]), "k");
+
+        // There is no velar fricative, so `k` is left unchanged.
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].warning);
+        match &errs[0].kind {
+            ErrorKind::NoSegment(span, desc) => {
+                assert_eq!(span.as_str(), "[+fricative]");
+                assert_eq!(desc, "k");
+            },
+            err => panic!("expected missing segment, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn feature_change_positions() {
+        let terms = make_terms!("p", "t", "b", "d", "a");
+        let non_terms = Table::new(Vec::new());
+        let mut errs = Diagnostic::new();
+
+        let rule = compile_with(
+            "rule p t > [+voice] [+voice]",
+            &terms,
+            &non_terms,
+            &mut errs,
+        )
+        .unwrap();
+        assert_eq!(replace(&rule, &terms, &["a", "p", "t", "a"]), "bd");
+
+        let rule = compile_with(
+            "rule [+stop] a > [+voice]",
+            &terms,
+            &non_terms,
+            &mut errs,
+        )
+        .unwrap();
+        assert_eq!(replace(&rule, &terms, &["p", "a"]), "b");
+        assert_eq!(errs.as_slice().len(), 0);
+
+        for source in
+            &["rule p > [+voice] [+voice]", "rule [+stop]+ > [+voice]"]
+        {
+            let mut errs = Diagnostic::new();
+            let result = compile_with(source, &terms, &non_terms, &mut errs);
+            assert!(result.is_err());
+            let errs = errs.take_errors();
+            assert_eq!(errs.len(), 1);
+            match &errs[0].kind {
+                ErrorKind::UnalignedMatrix(span) => {
+                    assert_eq!(span.as_str(), "[+voice]");
+                },
+                err => panic!("expected unaligned matrix, found {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let mut errs = Diagnostic::new();
+        let result = compile_first(r"rule \V > (p | f | a)", &mut errs);
+
+        assert!(result.is_err());
+        let errs = errs.take_errors();
+        assert_eq!(errs.len(), 1);
+        match &errs[0].kind {
+            ErrorKind::ArityMismatch(span, 3, target, 2) => {
+                assert_eq!(span.as_str(), "(p | f | a)");
+                assert_eq!(target.as_str(), r"\V");
+            },
+            err => panic!("expected arity mismatch, found {:?}", err),
+        }
+
+        let mut errs = Diagnostic::new();
+        let result = compile_first(r"rule p a > (f | i)", &mut errs);
+        assert!(result.is_err());
+        assert_eq!(errs.as_slice().len(), 1);
+    }
+
+    #[test]
+    fn undefined_names() {
+        let mut errs = Diagnostic::new();
+        let result = compile_first(r"rule p > b / \C _ \V", &mut errs);
+
+        assert!(result.is_err());
+        assert_eq!(errs.as_slice().len(), 2);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::symbol::{Table, Terminal, Word};
+use std::{error::Error, fmt};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentError {
+    pub input: String,
+    pub offset: usize,
+    pub grapheme: String,
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "no alphabet symbol for `{}` at grapheme {} of `{}`",
+            self.grapheme, self.offset, self.input
+        )
+    }
+}
+
+impl Error for SegmentError {}
+
+pub fn segment(
+    input: &str,
+    terms: &Table<Terminal>,
+) -> Result<Word, SegmentError> {
+    let mut bounds =
+        input.grapheme_indices(true).map(|(idx, _)| idx).collect::<Vec<_>>();
+    bounds.push(input.len());
+
+    let graphemes = bounds.len() - 1;
+    let mut symbols = Vec::new();
+    let mut pos = 0;
+
+    while pos < graphemes {
+        let found = (pos + 1 ..= graphemes).rev().find_map(|end| {
+            terms
+                .find(&input[bounds[pos] .. bounds[end]])
+                .map(|term| (term, end))
+        });
+
+        match found {
+            Some((term, end)) => {
+                symbols.push(term.clone());
+                pos = end;
+            },
+            None => {
+                return Err(SegmentError {
+                    input: input.to_owned(),
+                    offset: pos,
+                    grapheme: input[bounds[pos] .. bounds[pos + 1]].to_owned(),
+                })
+            },
+        }
+    }
+
+    Ok(Word { symbols })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{segment, SegmentError};
+    use crate::{find_syms, make_terms, symbol::Word};
+
+    #[test]
+    fn longest_match() {
+        let terms = make_terms!("t", "t̠ʃ", "a", "ŋ", "k", "k͡p", "d", "dz");
+
+        let word = segment("t̠ʃaŋka", &terms).unwrap();
+        let expected =
+            find_syms!(&terms, Clone::clone, "t̠ʃ", "a", "ŋ", "k", "a");
+        let expected = expected.unwrap();
+        assert_eq!(word, Word { symbols: expected });
+
+        let word = segment("k͡padzat", &terms).unwrap();
+        let expected =
+            find_syms!(&terms, Clone::clone, "k͡p", "a", "dz", "a", "t")
+                .unwrap();
+        assert_eq!(word, Word { symbols: expected });
+
+        let word = segment("dka", &terms).unwrap();
+        let expected = find_syms!(&terms, Clone::clone, "d", "k", "a").unwrap();
+        assert_eq!(word, Word { symbols: expected });
+
+        assert_eq!(segment("", &terms).unwrap(), Word { symbols: vec![] });
+    }
+
+    #[test]
+    fn unsegmentable() {
+        let terms = make_terms!("t", "a", "k");
+
+        assert_eq!(
+            segment("tãka", &terms),
+            Err(SegmentError {
+                input: "tãka".to_owned(),
+                offset: 1,
+                grapheme: "ã".to_owned(),
+            })
+        );
+    }
+}
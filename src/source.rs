@@ -70,6 +70,10 @@ impl Src {
         self.inner.segments.len() - 1
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn content(&self) -> &str {
         &self.inner.content
     }
@@ -45,8 +45,8 @@ impl fmt::Debug for Location {
         let (line, column) = self.line_column();
         write!(
             fmtr,
-            "Location {} src: {:?}, pos: {}, line: {}, column: {} {}",
-            '{', self.src, self.pos, line, column, '}'
+            "Location {{ src: {:?}, pos: {}, line: {}, column: {} }}",
+            self.src, self.pos, line, column
         )
     }
 }
@@ -53,6 +53,7 @@ impl Reader {
         self.marked = self.pos;
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> bool {
         self.advance(1) == 1
     }
@@ -30,6 +30,10 @@ impl Span {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn src(&self) -> &Src {
         self.loc.src()
     }
@@ -42,19 +46,23 @@ impl Span {
     pub fn content(&self) -> SpanContent {
         SpanContent { span: self.clone() }
     }
+
+    pub fn join(&self, other: &Self) -> Self {
+        let start = self.loc.pos().min(other.loc.pos());
+        let end = self.end().pos().max(other.end().pos());
+        Span::new(Location::new(self.src().clone(), start), end - start)
+    }
 }
 
 impl fmt::Debug for Span {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmtr,
-            "Span {} src: {:?}, start: {}, end: {}, content: {:?} {}",
-            '{',
+            "Span {{ src: {:?}, start: {}, end: {}, content: {:?} }}",
             self.src(),
             self.start(),
             self.end(),
             self.as_str(),
-            '}'
         )
     }
 }
@@ -9,7 +9,7 @@ pub trait DescKey {
     fn desc(&self) -> &str;
 
     fn cmp_desc(&self, other: &Self) -> Ordering {
-        self.desc().cmp(&other.desc())
+        self.desc().cmp(other.desc())
     }
 }
 
@@ -17,6 +17,7 @@ pub trait DescKey {
 pub enum Symbol {
     Term(Terminal),
     NonTerm(NonTerminal),
+    Seq(Sequence),
 }
 
 impl DescKey for Symbol {
@@ -24,6 +25,7 @@ impl DescKey for Symbol {
         match self {
             Symbol::Term(term) => term.desc(),
             Symbol::NonTerm(nonterm) => nonterm.desc(),
+            Symbol::Seq(seq) => seq.desc(),
         }
     }
 }
@@ -41,7 +43,7 @@ impl PartialEq for Terminal {
 
 impl PartialOrd for Terminal {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.ptr().partial_cmp(&other.ptr())
+        Some(self.cmp(other))
     }
 }
 
@@ -82,6 +84,43 @@ impl fmt::Display for Terminal {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sequence {
+    terms: Rc<[Terminal]>,
+    desc: Rc<str>,
+}
+
+impl DescKey for Sequence {
+    fn desc(&self) -> &str {
+        &self.desc
+    }
+}
+
+impl Sequence {
+    pub fn new<V>(terms: V) -> Self
+    where
+        V: Into<Rc<[Terminal]>>,
+    {
+        let terms = terms.into();
+        let desc = terms.iter().map(DescKey::desc).collect::<String>();
+        Self { terms, desc: Rc::from(desc) }
+    }
+
+    pub fn terms(&self) -> &[Terminal] {
+        &self.terms
+    }
+
+    pub fn is_single(&self, term: &Terminal) -> bool {
+        matches!(&*self.terms, [only] if only == term)
+    }
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.desc())
+    }
+}
+
 #[derive(Debug)]
 struct NonTerminalInner {
     members: Box<[Symbol]>,
@@ -103,7 +142,7 @@ impl Eq for NonTerminal {}
 
 impl PartialOrd for NonTerminal {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.ptr().partial_cmp(&other.ptr())
+        Some(self.cmp(other))
     }
 }
 
@@ -147,6 +186,7 @@ impl NonTerminal {
             match member {
                 Symbol::Term(other) if other == term => return true,
                 Symbol::NonTerm(other) if other.contains(term) => return true,
+                Symbol::Seq(other) if other.is_single(term) => return true,
                 _ => (),
             }
         }
@@ -154,6 +194,124 @@ impl NonTerminal {
         false
     }
 
+    pub fn leaves(&self) -> Vec<Symbol> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<Symbol>) {
+        for member in self.members() {
+            match member {
+                Symbol::NonTerm(other) => other.collect_leaves(leaves),
+                _ => {
+                    if !leaves.contains(member) {
+                        leaves.push(member.clone());
+                    }
+                },
+            }
+        }
+    }
+
+    fn has_leaf(&self, leaf: &Symbol) -> bool {
+        match leaf {
+            Symbol::Term(term) => self.contains(term),
+            Symbol::Seq(seq) => match seq.terms() {
+                [only] => self.contains(only),
+                _ => self.leaves().contains(leaf),
+            },
+            Symbol::NonTerm(other) => other.is_subset(self),
+        }
+    }
+
+    pub fn union<S>(&self, other: &NonTerminal, desc: S) -> NonTerminal
+    where
+        S: Into<Box<str>>,
+    {
+        let mut members = self.leaves();
+        for leaf in other.leaves() {
+            if !self.has_leaf(&leaf) {
+                members.push(leaf);
+            }
+        }
+        NonTerminal::new(desc, members)
+    }
+
+    pub fn intersection<S>(&self, other: &NonTerminal, desc: S) -> NonTerminal
+    where
+        S: Into<Box<str>>,
+    {
+        let members = self
+            .leaves()
+            .into_iter()
+            .filter(|leaf| other.has_leaf(leaf))
+            .collect::<Vec<_>>();
+        NonTerminal::new(desc, members)
+    }
+
+    pub fn difference<S>(&self, other: &NonTerminal, desc: S) -> NonTerminal
+    where
+        S: Into<Box<str>>,
+    {
+        let members = self
+            .leaves()
+            .into_iter()
+            .filter(|leaf| !other.has_leaf(leaf))
+            .collect::<Vec<_>>();
+        NonTerminal::new(desc, members)
+    }
+
+    pub fn is_subset(&self, other: &NonTerminal) -> bool {
+        self.leaves().iter().all(|leaf| other.has_leaf(leaf))
+    }
+
+    pub fn set_eq(&self, other: &NonTerminal) -> bool {
+        self.is_subset(other) && other.is_subset(self)
+    }
+
+    pub fn complement<S>(
+        &self,
+        alphabet: &Table<Terminal>,
+        desc: S,
+    ) -> NonTerminal
+    where
+        S: Into<Box<str>>,
+    {
+        let members = alphabet
+            .as_slice()
+            .iter()
+            .filter(|term| !self.contains(term))
+            .map(|term| Symbol::Term(term.clone()))
+            .collect::<Vec<_>>();
+        NonTerminal::new(desc, members)
+    }
+
+    pub fn prefix_lens(&self, terms: &[Terminal]) -> Vec<usize> {
+        let mut lens = Vec::new();
+        self.collect_prefix_lens(terms, &mut lens);
+        lens
+    }
+
+    fn collect_prefix_lens(&self, terms: &[Terminal], lens: &mut Vec<usize>) {
+        for member in self.members() {
+            let len = match member {
+                Symbol::Term(other) if terms.first() == Some(other) => 1,
+                Symbol::Seq(other) if terms.starts_with(other.terms()) => {
+                    other.terms().len()
+                },
+                Symbol::NonTerm(other) => {
+                    other.collect_prefix_lens(terms, lens);
+                    continue;
+                },
+                _ => continue,
+            };
+
+            if !lens.contains(&len) {
+                lens.push(len);
+            }
+        }
+    }
+
     pub fn members(&self) -> &[Symbol] {
         &self.inner.members
     }
@@ -217,6 +375,15 @@ pub struct Word {
     pub symbols: Vec<Terminal>,
 }
 
+impl fmt::Display for Word {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for symbol in &self.symbols {
+            write!(fmt, "{}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
 #[macro_export]
 macro_rules! make_terms {
     ($($desc:expr),*) => {
@@ -237,3 +404,58 @@ macro_rules! find_syms {
         builder()
     }};
 }
+
+#[cfg(test)]
+mod test {
+    use super::{DescKey, NonTerminal, Symbol, Terminal};
+
+    fn wrap(term: &Terminal) -> Symbol {
+        Symbol::Term(term.clone())
+    }
+
+    fn descs(non_term: &NonTerminal) -> Vec<&str> {
+        non_term.members().iter().map(DescKey::desc).collect()
+    }
+
+    #[test]
+    fn set_algebra() {
+        let terms = make_terms!("b", "d", "m", "p", "t");
+        let voiced = find_syms!(&terms, wrap, "b", "d", "m").unwrap();
+        let voiced = NonTerminal::new("Voiced", voiced);
+        let stops = find_syms!(&terms, wrap, "p", "t", "b", "d").unwrap();
+        let stops = NonTerminal::new("Stop", stops);
+
+        let union = voiced.union(&stops, "Voiced | Stop");
+        assert_eq!(descs(&union), ["b", "d", "m", "p", "t"]);
+
+        let intersection = voiced.intersection(&stops, "Voiced & Stop");
+        assert_eq!(descs(&intersection), ["b", "d"]);
+
+        let difference = stops.difference(&voiced, "Stop - Voiced");
+        assert_eq!(descs(&difference), ["p", "t"]);
+
+        assert!(intersection.is_subset(&voiced));
+        assert!(intersection.is_subset(&stops));
+        assert!(!voiced.is_subset(&stops));
+        assert!(union.set_eq(&stops.union(&voiced, "Stop | Voiced")));
+        assert!(!union.set_eq(&voiced));
+    }
+
+    #[test]
+    fn nested_members() {
+        let terms = make_terms!("a", "i", "u");
+        let front =
+            NonTerminal::new("F", find_syms!(&terms, wrap, "i").unwrap());
+        let mut vowels = find_syms!(&terms, wrap, "a", "u").unwrap();
+        vowels.push(Symbol::NonTerm(front.clone()));
+        let vowels = NonTerminal::new("V", vowels);
+
+        assert_eq!(
+            descs(&NonTerminal::new("L", vowels.leaves())),
+            ["a", "u", "i"]
+        );
+        assert!(front.is_subset(&vowels));
+        let back = vowels.difference(&front, "V - F");
+        assert_eq!(descs(&back), ["a", "u"]);
+    }
+}
@@ -5,6 +5,14 @@ use std::fmt::{self, Write};
 pub enum TokenKind {
     Alphabet,
     Class,
+    Feature,
+    Rule,
+    Sequential,
+    Simultaneous,
+    Iterative,
+    Ltr,
+    Rtl,
+    Feeding,
     String(String),
     ClassIdent(String),
     Eq,
@@ -12,13 +20,39 @@ pub enum TokenKind {
     Pipe,
     OpenParen,
     CloseParen,
+    Greater,
+    Slash,
+    Underscore,
+    Boundary,
+    Question,
+    Star,
+    Plus,
+    OpenBrace,
+    CloseBrace,
+    Minus,
+    Ampersand,
+    Bang,
+    Null,
+    Index(usize),
+    At,
+    OpenBracket,
+    CloseBracket,
     Eof,
 }
 
 impl fmt::Display for TokenKind {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         fmtr.write_str(match self {
-            TokenKind::Alphabet | TokenKind::Class => "keyword",
+            TokenKind::Alphabet
+            | TokenKind::Class
+            | TokenKind::Feature
+            | TokenKind::Rule
+            | TokenKind::Sequential
+            | TokenKind::Simultaneous
+            | TokenKind::Iterative
+            | TokenKind::Ltr
+            | TokenKind::Rtl
+            | TokenKind::Feeding => "keyword",
             TokenKind::String(_) => "string",
             TokenKind::ClassIdent(_) => "class",
             TokenKind::Eq => "equal symbol",
@@ -26,6 +60,23 @@ impl fmt::Display for TokenKind {
             TokenKind::Pipe => "pipe",
             TokenKind::OpenParen => "opening parenthesis",
             TokenKind::CloseParen => "closing parenthesis",
+            TokenKind::Greater => "greater-than symbol",
+            TokenKind::Slash => "slash",
+            TokenKind::Underscore => "underscore",
+            TokenKind::Boundary => "word boundary",
+            TokenKind::Question => "question mark",
+            TokenKind::Star => "asterisk",
+            TokenKind::Plus => "plus sign",
+            TokenKind::OpenBrace => "opening brace",
+            TokenKind::CloseBrace => "closing brace",
+            TokenKind::Minus => "minus sign",
+            TokenKind::Ampersand => "ampersand",
+            TokenKind::Bang => "exclamation mark",
+            TokenKind::Null => "null symbol",
+            TokenKind::Index(_) => "index",
+            TokenKind::At => "at sign",
+            TokenKind::OpenBracket => "opening bracket",
+            TokenKind::CloseBracket => "closing bracket",
             TokenKind::Eof => "end of input",
         })
     }
@@ -60,7 +111,7 @@ impl TokenPattern for TokenKind {
     }
 }
 
-impl<'content> TokenPattern for (TokenKind, &'content str) {
+impl TokenPattern for (TokenKind, &str) {
     fn test(&self, tok: &Token) -> bool {
         tok.kind == self.0 && tok.span.as_str() == self.1
     }
@@ -94,7 +145,7 @@ where
     }
 }
 
-impl<'pat, P> TokenPattern for &'pat P
+impl<P> TokenPattern for &P
 where
     P: TokenPattern + ?Sized,
 {
@@ -106,3 +157,85 @@ where
         (**self).render(pieces)
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyString;
+
+impl TokenPattern for AnyString {
+    fn test(&self, tok: &Token) -> bool {
+        matches!(tok.kind, TokenKind::String(_))
+    }
+
+    fn render<'buf>(&self, pieces: &mut SeqFmt<'buf>) -> fmt::Result {
+        pieces.mark_start()?;
+        pieces.write_str("string")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyClassIdent;
+
+impl TokenPattern for AnyClassIdent {
+    fn test(&self, tok: &Token) -> bool {
+        matches!(tok.kind, TokenKind::ClassIdent(_))
+    }
+
+    fn render<'buf>(&self, pieces: &mut SeqFmt<'buf>) -> fmt::Result {
+        pieces.mark_start()?;
+        pieces.write_str("class")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyIndex;
+
+impl TokenPattern for AnyIndex {
+    fn test(&self, tok: &Token) -> bool {
+        matches!(tok.kind, TokenKind::Index(_))
+    }
+
+    fn render<'buf>(&self, pieces: &mut SeqFmt<'buf>) -> fmt::Result {
+        pieces.mark_start()?;
+        pieces.write_str("index")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyNumber;
+
+impl TokenPattern for AnyNumber {
+    fn test(&self, tok: &Token) -> bool {
+        match &tok.kind {
+            TokenKind::String(content) => {
+                !content.is_empty()
+                    && content.chars().all(|ch| ch.is_ascii_digit())
+            },
+            _ => false,
+        }
+    }
+
+    fn render<'buf>(&self, pieces: &mut SeqFmt<'buf>) -> fmt::Result {
+        pieces.mark_start()?;
+        pieces.write_str("number")
+    }
+}
+
+// `-` and `0` also stand for the null symbol, but only where they make up a
+// whole side of a rule, since they are operators and symbols elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyNull;
+
+impl TokenPattern for AnyNull {
+    fn test(&self, tok: &Token) -> bool {
+        match &tok.kind {
+            TokenKind::Null | TokenKind::Minus => true,
+            TokenKind::String(_) => tok.span.as_str() == "0",
+            _ => false,
+        }
+    }
+
+    fn render<'buf>(&self, pieces: &mut SeqFmt<'buf>) -> fmt::Result {
+        pieces.mark_start()?;
+        pieces.write_str("null symbol")
+    }
+}